@@ -1,18 +1,55 @@
 use crate::app::settings::Settings;
+use std::time::Duration;
 use threadpool::ThreadPool;
 
+/// How downloads are checked and how they deal with failing connections
+#[derive(Clone, Copy, Debug)]
+pub struct DownloadOptions {
+    pub skip_verify: bool,
+    /// How many times a download that fails is retried, waiting twice as long each time up to a minute
+    pub retries: u32,
+    /// How long to wait to connect or for more data before a download attempt fails
+    pub timeout: Duration,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        DownloadOptions {
+            skip_verify: false,
+            retries: 3,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
 pub struct App {
-    pub current_download_progress: u8,
     pub settings: Settings,
+    /// The threads that mods are downloaded on
     pub pool: ThreadPool,
+    pub download_options: DownloadOptions,
+    /// The name of the installation that commands act on
+    pub install_dir: Option<String>,
+    /// Whether to use the cached mod links instead of fetching them
+    pub offline: bool,
+    /// Whether to answer yes to confirmations and fail instead of asking for anything else
+    pub non_interactive: bool,
+    /// The URLs to fetch the mod links from, in order of preference
+    pub mod_links_urls: Vec<String>,
+    /// The URLs to fetch the API links from, in order of preference
+    pub api_links_urls: Vec<String>,
 }
 
 impl Default for App {
     fn default() -> Self {
         App {
-            current_download_progress: 0,
             settings: Settings::default(),
             pool: ThreadPool::new(num_cpus::get()),
+            download_options: DownloadOptions::default(),
+            install_dir: None,
+            offline: false,
+            non_interactive: false,
+            mod_links_urls: Vec::new(),
+            api_links_urls: Vec::new(),
         }
     }
-}
\ No newline at end of file
+}
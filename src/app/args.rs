@@ -1,29 +1,235 @@
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Subcommand, Debug)]
+pub enum CacheCommand {
+    /// List the cached archives
+    List,
+    /// Show how much space the cached archives take up
+    Size,
+    /// Delete every cached archive
+    Clean,
+    /// Delete the oldest cached archives until the cache fits in its size limit
+    Prune {
+        /// The size limit in MiB; defaults to the one in settings
+        #[clap(long, value_name = "MIB")]
+        max_size: Option<u64>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum InstallDirCommand {
+    /// List the named game installations
+    List,
+    /// Add a game installation under a name
+    Add {
+        name: String,
+        /// The folder that contains the Hollow Knight executable
+        #[clap(value_hint = clap::ValueHint::DirPath)]
+        path: String,
+        /// Also make it the default installation
+        #[clap(long)]
+        default: bool,
+    },
+    /// Forget a game installation; Its files are left alone
+    Remove { name: String },
+    /// Set the installation that is used when none is selected with --install
+    Default { name: String },
+}
 
 #[derive(Subcommand, Debug)]
 pub enum SubCommand {
-    /// Install a mod or enable the Modding API
-    Add { query: String },
+    /// Install a mod, enable the Modding API or create a profile
+    Add {
+        query: String,
+        /// The mods to put in a new profile instead of asking for them; pass an empty value
+        /// for a profile without mods
+        #[clap(long, value_delimiter = ',', value_name = "MOD")]
+        mods: Option<Vec<String>>,
+    },
+    /// Uninstall mods that were only installed as dependencies and are no longer needed
+    Autoremove {
+        /// List the mods that would be uninstalled without uninstalling them
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Manage the cache of downloaded archives
+    Cache {
+        #[clap(subcommand)]
+        cmd: CacheCommand,
+    },
     /// Get detailed info on a mod
     Info { query: String },
+    /// Install the mods of the current profile and the Modding API
+    Install {
+        /// Install the exact mods and Modding API recorded in the lockfile, or fail
+        #[clap(long)]
+        locked: bool,
+        /// The lockfile to install from
+        #[clap(
+            long,
+            default_value = "hkdl.lock",
+            value_name = "PATH",
+            value_hint = clap::ValueHint::FilePath,
+            requires = "locked"
+        )]
+        lockfile: PathBuf,
+    },
+    /// Manage the named game installations
+    InstallDirs {
+        #[clap(subcommand)]
+        cmd: InstallDirCommand,
+    },
     /// Fetch a list of mods
-    List { 
+    List {
         #[clap(default_value = "")]
         filter: Option<String>,
     },
+    /// Record the exact versions, URLs and SHA256 of a profile's mods and of the Modding API
+    /// in a lockfile
+    Lock {
+        /// The profile to lock; defaults to the current profile
+        #[clap(long)]
+        profile: Option<String>,
+        /// The lockfile to write
+        #[clap(
+            long,
+            default_value = "hkdl.lock",
+            value_name = "PATH",
+            value_hint = clap::ValueHint::FilePath
+        )]
+        lockfile: PathBuf,
+    },
+    /// List installed mods that have newer versions available and mods that are new
+    /// since outdated last ran; Exits with code 1 if any mod with a known version is outdated
+    Outdated,
+    /// Pin a mod in a profile to a version or constraint such as `1.2.0.0` or `>=1.2, <2`;
+    /// Mods not in the profile yet are added to it
+    Pin {
+        query: String,
+        /// The version or constraint; defaults to the installed version
+        constraint: Option<String>,
+        /// The profile to change; defaults to the current profile
+        #[clap(long)]
+        profile: Option<String>,
+        /// Remove the pin instead
+        #[clap(long, conflicts_with = "constraint")]
+        remove: bool,
+    },
     /// Uninstall a mod or disable the Modding API
-    Rm { query: String },
+    Rm {
+        query: String,
+        /// Also uninstall the installed mods that depend on the mod
+        #[clap(long)]
+        cascade: bool,
+    },
     /// Set path to game directory
     SetPath {
         #[clap(value_hint = clap::ValueHint::DirPath)]
         path: String,
     },
+    /// Show the installation in use, its game version and the state of its Modding API and mods
+    Status,
     /// Update a mod or the Modding API
-    Update { query: String },
+    Update {
+        #[clap(required_unless_present = "all")]
+        query: Option<String>,
+        /// Update the Modding API and every installed mod
+        #[clap(long, conflicts_with = "query")]
+        all: bool,
+    },
+    /// Check the files of installed mods and the Modding API against what was installed;
+    /// Exits with code 1 if any were modified, are missing or were added
+    Verify {
+        /// The mod to check, or "api"; checks everything if left out
+        query: Option<String>,
+        /// Reinstall the mods and the Modding API whose files do not match from the archives
+        /// they were installed from; Files added to a mod's folder are removed
+        #[clap(long)]
+        repair: bool,
+    },
 }
 
 #[derive(Parser, Debug)]
 pub struct Arguments {
     #[clap(subcommand)]
     pub cmd: SubCommand,
-}
\ No newline at end of file
+    /// Answer yes to every confirmation and fail instead of asking for anything else
+    #[clap(long, short = 'y', visible_alias = "non-interactive", global = true)]
+    pub yes: bool,
+    /// The folder that contains the Hollow Knight executable; Sets the game path of the
+    /// installation instead of detecting it or asking for it
+    #[clap(
+        long,
+        global = true,
+        env = "HKDL_GAME_PATH",
+        value_name = "PATH",
+        value_hint = clap::ValueHint::DirPath
+    )]
+    pub game_path: Option<String>,
+    /// The profile to create, delete or switch to instead of asking for its name
+    #[clap(long, global = true, value_name = "NAME")]
+    pub profile_name: Option<String>,
+    /// The named game installation to act on; defaults to the default installation
+    #[clap(long, global = true, env = "HKDL_INSTALL", value_name = "NAME")]
+    pub install: Option<String>,
+    /// Install downloaded mods without checking their SHA256 against ModLinks;
+    /// only meant for local testing
+    #[clap(long, global = true)]
+    pub skip_verify: bool,
+    /// How many times to retry a download that fails; defaults to 3
+    #[clap(
+        long,
+        global = true,
+        env = "HKDL_RETRIES",
+        value_name = "COUNT",
+        value_parser = clap::value_parser!(u32).range(..=100)
+    )]
+    pub retries: Option<u32>,
+    /// How many seconds to wait to connect or for more data before a download attempt fails;
+    /// defaults to 30
+    #[clap(
+        long,
+        global = true,
+        env = "HKDL_TIMEOUT",
+        value_name = "SECONDS",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub timeout: Option<u64>,
+    /// The maximum number of mods to download at once; defaults to the number of CPUs
+    #[clap(
+        long,
+        short = 'j',
+        global = true,
+        env = "HKDL_JOBS",
+        value_parser = clap::value_parser!(u16).range(1..)
+    )]
+    pub jobs: Option<u16>,
+    /// Use the cached ModLinks and ApiLinks instead of fetching them
+    #[clap(long, global = true)]
+    pub offline: bool,
+    /// Fetch ModLinks from this URL or file:// path instead of the one in settings
+    #[clap(long, global = true, env = "HKDL_MODLINKS_URL", value_name = "URL")]
+    pub modlinks_url: Option<String>,
+    /// Fallback URLs for ModLinks, tried in order; replaces the mirrors in settings
+    #[clap(
+        long = "modlinks-mirror",
+        global = true,
+        env = "HKDL_MODLINKS_MIRRORS",
+        value_delimiter = ',',
+        value_name = "URL"
+    )]
+    pub modlinks_mirrors: Vec<String>,
+    /// Fetch ApiLinks from this URL or file:// path instead of the one in settings
+    #[clap(long, global = true, env = "HKDL_APILINKS_URL", value_name = "URL")]
+    pub apilinks_url: Option<String>,
+    /// Fallback URLs for ApiLinks, tried in order; replaces the mirrors in settings
+    #[clap(
+        long = "apilinks-mirror",
+        global = true,
+        env = "HKDL_APILINKS_MIRRORS",
+        value_delimiter = ',',
+        value_name = "URL"
+    )]
+    pub apilinks_mirrors: Vec<String>,
+}
@@ -0,0 +1,28 @@
+use crate::app::installed::{InstalledFile, InstalledMod};
+use serde::{Deserialize, Serialize};
+
+/// The name given to the installation that settings from before named installations are
+/// moved into
+pub const DEFAULT_INSTALL_DIR: &str = "default";
+
+/// A named game installation with the state of the mods and Modding API installed in it
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct InstallDir {
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Mods Path")]
+    pub mods_path: String,
+    #[serde(rename = "Installed Mods", default)]
+    pub installed_mods: Vec<InstalledMod>,
+    #[serde(rename = "API Version", default)]
+    pub api_version: String,
+    /// The SHA256 of the installed Modding API release's archive
+    #[serde(rename = "API SHA256", default)]
+    pub api_sha256: String,
+    /// The download link of the installed Modding API release
+    #[serde(rename = "API URL", default)]
+    pub api_url: String,
+    /// The Modding API's files as installed, relative to the Managed folder
+    #[serde(rename = "API Files", default)]
+    pub api_files: Vec<InstalledFile>,
+}
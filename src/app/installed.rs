@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+/// A record of a mod installed by hkdl, kept apart from the fetched mod links so that
+/// it survives the catalog being refreshed
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct InstalledMod {
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Version")]
+    pub version: String,
+    #[serde(rename = "SHA256")]
+    pub sha256: String,
+    /// The download link of the installed release; Empty for mods installed before it was kept
+    #[serde(rename = "URL", default)]
+    pub url: String,
+    /// Seconds since the Unix epoch at which the mod was installed
+    #[serde(rename = "Installed At")]
+    pub installed_at: u64,
+    /// The installed files, relative to the mod's folder
+    #[serde(rename = "Files", default)]
+    pub files: Vec<InstalledFile>,
+    /// Whether the mod was asked for by the user rather than pulled in as a dependency; Records
+    /// from before this was tracked count as explicit so they are never autoremoved
+    #[serde(rename = "Explicit", default = "default_explicit")]
+    pub explicit: bool,
+}
+
+fn default_explicit() -> bool {
+    true
+}
+
+/// A file put on disk by an installation, with its SHA256 at the time
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(from = "FileRecord")]
+pub struct InstalledFile {
+    #[serde(rename = "Path")]
+    pub path: String,
+    /// Empty for files recorded before their hashes were kept
+    #[serde(rename = "SHA256")]
+    pub sha256: String,
+}
+
+/// The forms an installed file has been saved in; Older settings only kept its path
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FileRecord {
+    Path(String),
+    File {
+        #[serde(rename = "Path")]
+        path: String,
+        #[serde(rename = "SHA256", default)]
+        sha256: String,
+    },
+}
+
+impl From<FileRecord> for InstalledFile {
+    fn from(record: FileRecord) -> Self {
+        match record {
+            FileRecord::Path(path) => InstalledFile {
+                path,
+                sha256: String::new(),
+            },
+            FileRecord::File { path, sha256 } => InstalledFile { path, sha256 },
+        }
+    }
+}
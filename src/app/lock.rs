@@ -0,0 +1,85 @@
+use crate::app::profile::ProfileMod;
+use crate::detect::Platform;
+use crate::mod_links::api::ApiRelease;
+use serde::{Deserialize, Serialize};
+
+/// The version of the lockfile format written by this version of hkdl; Version 1 only kept the
+/// Modding API of the platform it was written on
+pub const LOCKFILE_VERSION: u32 = 2;
+
+/// The exact mods and Modding API that a profile resolved to, so that it can be installed the
+/// same way on another machine
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LockFile {
+    #[serde(rename = "Lockfile Version")]
+    pub lockfile_version: u32,
+    #[serde(rename = "Profile")]
+    pub profile: String,
+    /// The mods listed in the profile itself and their pins, without the dependencies it
+    /// pulled in
+    #[serde(rename = "Profile Mods")]
+    pub profile_mods: Vec<ProfileMod>,
+    #[serde(rename = "API")]
+    pub api: LockedApi,
+    /// Every mod to install, each after the mods it depends on
+    #[serde(rename = "Mods")]
+    pub mods: Vec<LockedMod>,
+}
+
+/// An exact release of the Modding API, with the archive of every platform so that the lockfile
+/// can be installed on any of them
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LockedApi {
+    #[serde(rename = "Version")]
+    pub version: String,
+    #[serde(rename = "Linux")]
+    pub linux: LockedApiLink,
+    #[serde(rename = "Mac")]
+    pub mac: LockedApiLink,
+    #[serde(rename = "Windows")]
+    pub windows: LockedApiLink,
+    /// The files the API replaces in the Managed folder
+    #[serde(rename = "Files")]
+    pub files: Vec<String>,
+}
+
+impl LockedApi {
+    /// Get the release of the Modding API for a build of the game
+    /// # Arguments
+    /// * `platform` - The platform the game is built for
+    pub fn release(&self, platform: Platform) -> ApiRelease {
+        let link = match platform {
+            Platform::Linux => &self.linux,
+            Platform::Mac => &self.mac,
+            Platform::Windows => &self.windows,
+        };
+        ApiRelease {
+            version: self.version.clone(),
+            url: link.url.clone(),
+            sha256: link.sha256.clone(),
+            files: self.files.clone(),
+        }
+    }
+}
+
+/// The archive of a release of the Modding API for one platform
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LockedApiLink {
+    #[serde(rename = "URL")]
+    pub url: String,
+    #[serde(rename = "SHA256")]
+    pub sha256: String,
+}
+
+/// An exact release of a mod
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LockedMod {
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Version")]
+    pub version: String,
+    #[serde(rename = "URL")]
+    pub url: String,
+    #[serde(rename = "SHA256")]
+    pub sha256: String,
+}
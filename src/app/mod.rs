@@ -1,4 +1,9 @@
+#[allow(clippy::module_inception)]
 pub mod app;
 pub mod args;
+pub mod install_dir;
+pub mod installed;
+pub mod lock;
 pub mod profile;
-pub mod settings;
\ No newline at end of file
+pub mod settings;
+pub mod source;
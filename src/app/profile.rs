@@ -3,5 +3,53 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Profile {
     pub name: String,
-    pub mods: Vec<String>,
+    pub mods: Vec<ProfileMod>,
+}
+
+/// A mod in a profile, optionally pinned to the versions that satisfy a constraint
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(from = "ProfileModRecord", into = "ProfileModRecord")]
+pub struct ProfileMod {
+    pub name: String,
+    /// A version or constraint such as `1.2.0.0` or `>=1.2, <2` that the mod is held to
+    pub pin: Option<String>,
+}
+
+impl ProfileMod {
+    pub fn new(name: String) -> Self {
+        ProfileMod { name, pin: None }
+    }
+}
+
+/// The forms a profile mod is saved in; Mods without a pin are saved as just their name, the
+/// way every mod was saved before they could be pinned
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum ProfileModRecord {
+    Name(String),
+    Pinned { name: String, pin: String },
+}
+
+impl From<ProfileModRecord> for ProfileMod {
+    fn from(record: ProfileModRecord) -> Self {
+        match record {
+            ProfileModRecord::Name(name) => ProfileMod::new(name),
+            ProfileModRecord::Pinned { name, pin } => ProfileMod {
+                name,
+                pin: Some(pin),
+            },
+        }
+    }
+}
+
+impl From<ProfileMod> for ProfileModRecord {
+    fn from(profile_mod: ProfileMod) -> Self {
+        match profile_mod.pin {
+            Some(pin) => ProfileModRecord::Pinned {
+                name: profile_mod.name,
+                pin,
+            },
+            None => ProfileModRecord::Name(profile_mod.name),
+        }
+    }
 }
@@ -1,29 +1,101 @@
+use crate::app::install_dir::InstallDir;
+use crate::app::installed::{InstalledFile, InstalledMod};
 use crate::app::profile::Profile;
+use crate::app::source::ModSource;
 use crate::mod_links::local::LocalModLinks;
 use serde::{Deserialize, Serialize};
 
+pub const API_URL: &str = "https://raw.githubusercontent.com/hk-modding/modlinks/main/ApiLinks.xml";
+pub const MOD_URL: &str = "https://raw.githubusercontent.com/hk-modding/modlinks/main/ModLinks.xml";
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Settings {
+    #[serde(rename = "API Links URL", default = "default_api_links_url")]
+    pub api_links_url: String,
+    /// URLs to try in order when the API links cannot be fetched from `api_links_url`
+    #[serde(rename = "API Links Mirrors", default)]
+    pub api_links_mirrors: Vec<String>,
+    /// The Modding API's files as installed, relative to the Managed folder
+    #[serde(rename = "API Files", default)]
+    pub api_files: Vec<InstalledFile>,
+    /// The SHA256 of the installed Modding API release's archive
+    #[serde(rename = "API SHA256", default)]
+    pub api_sha256: String,
+    /// The download link of the installed Modding API release
+    #[serde(rename = "API URL", default)]
+    pub api_url: String,
+    #[serde(rename = "API Version", default)]
+    pub api_version: String,
+    /// The size in MiB that the download cache is pruned down to, oldest archives first
+    #[serde(rename = "Cache Size Limit", default = "default_cache_size_limit")]
+    pub cache_size_limit: u64,
     #[serde(rename = "Current Profile")]
     pub current_profile: String,
+    /// The name of the installation that is used when none is selected
+    #[serde(rename = "Default Install Dir", default)]
+    pub default_install_dir: String,
+    /// The named game installations; The mods path, installed mods and Modding API fields
+    /// hold the state of the one that was used last
+    #[serde(rename = "Install Dirs", default)]
+    pub install_dirs: Vec<InstallDir>,
+    #[serde(rename = "Installed Mods", default)]
+    pub installed_mods: Vec<InstalledMod>,
     #[serde(rename = "Language")]
     pub language: String,
     #[serde(rename = "Mods Path")]
     pub mods_path: String,
     #[serde(rename = "Mod Links")]
     pub mod_links: LocalModLinks,
+    #[serde(rename = "Mod Links URL", default = "default_mod_links_url")]
+    pub mod_links_url: String,
+    /// URLs to try in order when the mod links cannot be fetched from `mod_links_url`
+    #[serde(rename = "Mod Links Mirrors", default)]
+    pub mod_links_mirrors: Vec<String>,
+    /// Additional mod feeds, merged in the order they are listed before the official
+    /// ModLinks; When several sources provide a mod of the same name, the first one wins
+    #[serde(rename = "Mod Sources", default)]
+    pub mod_sources: Vec<ModSource>,
     #[serde(rename = "Profiles")]
     pub profiles: Vec<Profile>,
+    /// The names of the mods in the mod links the last time `outdated` listed new mods
+    #[serde(rename = "Seen Mods", default)]
+    pub seen_mods: Vec<String>,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Settings {
+            api_links_url: default_api_links_url(),
+            api_links_mirrors: Vec::new(),
+            api_files: Vec::new(),
+            api_sha256: "".to_string(),
+            api_url: "".to_string(),
+            api_version: "".to_string(),
+            cache_size_limit: default_cache_size_limit(),
             current_profile: "".to_string(),
+            default_install_dir: "".to_string(),
+            install_dirs: Vec::new(),
+            installed_mods: Vec::new(),
             mods_path: "".to_string(),
             language: "English".to_string(),
             mod_links: LocalModLinks::default(),
+            mod_links_url: default_mod_links_url(),
+            mod_links_mirrors: Vec::new(),
+            mod_sources: Vec::new(),
             profiles: Vec::new(),
+            seen_mods: Vec::new(),
         }
     }
-}
\ No newline at end of file
+}
+
+fn default_api_links_url() -> String {
+    API_URL.to_string()
+}
+
+fn default_cache_size_limit() -> u64 {
+    512
+}
+
+fn default_mod_links_url() -> String {
+    MOD_URL.to_string()
+}
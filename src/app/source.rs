@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// An additional ModLinks feed, such as a private one for in-house mods
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ModSource {
+    /// The label shown next to the mods provided by this source
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "URL")]
+    pub url: String,
+    /// URLs to try in order when the feed cannot be fetched from `url`
+    #[serde(rename = "Mirrors", default)]
+    pub mirrors: Vec<String>,
+}
@@ -0,0 +1,591 @@
+use crate::steam;
+use crate::{STATIC_PATHS, SUFFIXES};
+use directories::BaseDirs;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where Hollow Knight is installed to inside of a Windows drive or Wine prefix, other than
+/// the locations in `STATIC_PATHS`
+const PREFIX_PATHS: [&str; 2] = ["GOG Games/Hollow Knight", "Games/Hollow Knight"];
+
+/// The launcher or store that an installation of Hollow Knight was found through
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Launcher {
+    Steam,
+    /// The Windows build run by Steam through Proton
+    Proton,
+    Gog,
+    Lutris,
+    Heroic,
+    /// A plain Wine prefix
+    Wine,
+}
+
+impl fmt::Display for Launcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Launcher::Steam => "Steam",
+            Launcher::Proton => "Steam (Proton)",
+            Launcher::Gog => "GOG",
+            Launcher::Lutris => "Lutris",
+            Launcher::Heroic => "Heroic",
+            Launcher::Wine => "Wine",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The operating system that a build of Hollow Knight is made for
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Platform {
+    Linux,
+    Mac,
+    Windows,
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Platform::Linux => "Linux",
+            Platform::Mac => "Mac",
+            Platform::Windows => "Windows",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// An installation of Hollow Knight that has a Managed folder
+#[derive(Clone, Debug, PartialEq)]
+pub struct Installation {
+    pub launcher: Launcher,
+    /// The folder that contains the game executable
+    pub game_path: PathBuf,
+    /// The Managed folder, in whichever of the `SUFFIXES` layouts the game uses
+    pub managed_path: PathBuf,
+}
+
+/// Find the Managed folder of a game folder by trying each of the `SUFFIXES` layouts
+/// # Arguments
+/// * `game_path` - The folder that contains the game executable
+pub fn managed_path(game_path: &Path) -> Option<PathBuf> {
+    SUFFIXES
+        .iter()
+        .map(|suffix| game_path.join(suffix))
+        .find(|path| path.is_dir())
+}
+
+/// Find the game folder of a Managed folder by stripping whichever of the `SUFFIXES` layouts it
+/// ends with
+/// # Arguments
+/// * `managed_path` - The path to the Managed folder
+pub fn game_path(managed_path: &Path) -> Option<PathBuf> {
+    SUFFIXES
+        .iter()
+        .map(Path::new)
+        .find(|suffix| managed_path.ends_with(suffix))
+        .and_then(|suffix| managed_path.ancestors().nth(suffix.components().count()))
+        .map(Path::to_path_buf)
+}
+
+/// Tell which platform the build of Hollow Knight in a Managed folder is made for from the files
+/// next to it, so that the Windows build run through Proton or Wine is not taken for a native
+/// one; Falls back to the platform this app runs on when there is no game executable to go by
+/// # Arguments
+/// * `managed_path` - The path to the Managed folder
+pub fn platform(managed_path: &Path) -> Option<Platform> {
+    if managed_path.ends_with("Contents/Resources/Data/Managed") {
+        return Some(Platform::Mac);
+    }
+    let data_name = managed_path
+        .parent()
+        .and_then(Path::file_name)
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_suffix("_Data"));
+    if let (Some(game_path), Some(data_name)) = (game_path(managed_path), data_name) {
+        if game_path.join(format!("{}.exe", data_name)).is_file() {
+            return Some(Platform::Windows);
+        }
+        if ["x86_64", "x86"].iter().any(|extension| {
+            game_path
+                .join(format!("{}.{}", data_name, extension))
+                .is_file()
+        }) {
+            return Some(Platform::Linux);
+        }
+    }
+
+    match env::consts::OS {
+        "linux" => Some(Platform::Linux),
+        "macos" => Some(Platform::Mac),
+        "windows" => Some(Platform::Windows),
+        _ => None,
+    }
+}
+
+/// Find every installation of Hollow Knight, looking in the configured Steam libraries, the
+/// usual install locations and, on Linux, the Proton and Wine prefixes and the installs of
+/// Lutris, Heroic and GOG
+pub fn installations() -> Vec<Installation> {
+    let base_dir = BaseDirs::new().unwrap();
+    let mut candidates: Vec<(Launcher, PathBuf)> = vec![];
+
+    let steam_roots = steam::steam_roots(&base_dir);
+    for game_path in steam::find_games(&steam_roots) {
+        // Steam keeps a prefix for the game only when it runs through Proton
+        let proton = game_path
+            .parent()
+            .and_then(Path::parent)
+            .map_or(false, |steamapps| {
+                steamapps.join("compatdata").join(steam::APP_ID).is_dir()
+            });
+        candidates.push((
+            if proton {
+                Launcher::Proton
+            } else {
+                Launcher::Steam
+            },
+            game_path,
+        ));
+    }
+
+    let static_roots: Vec<PathBuf> = if env::consts::OS == "windows" {
+        (b'A'..=b'Z')
+            .map(|letter| PathBuf::from(format!("{}:/", letter as char)))
+            .collect()
+    } else {
+        vec![base_dir.data_dir().to_path_buf()]
+    };
+    for root in static_roots {
+        candidates.extend(STATIC_PATHS.iter().map(|path| {
+            let launcher = if path.contains("GOG") {
+                Launcher::Gog
+            } else {
+                Launcher::Steam
+            };
+            (launcher, root.join(path))
+        }));
+    }
+
+    if env::consts::OS == "linux" {
+        candidates.extend(linux_candidates(&base_dir, &steam_roots));
+    }
+    if env::consts::OS == "macos" {
+        // The Managed folder is inside the app bundle in the game folder
+        candidates = candidates
+            .into_iter()
+            .flat_map(|(launcher, path)| {
+                [(launcher, path.join("hollow_knight.app")), (launcher, path)]
+            })
+            .collect();
+    }
+
+    let mut installations: Vec<Installation> = vec![];
+    for (launcher, game_path) in candidates {
+        log::info!("Checking {} path {:?}", launcher, game_path);
+        if let Some(managed) = managed_path(game_path.as_path()) {
+            // Unlike fs::canonicalize, this avoids verbatim \\?\ paths on Windows, which
+            // the mods path is later joined with `..` onto and those do not resolve
+            let managed = dunce::canonicalize(managed.as_path()).unwrap_or(managed);
+            if !installations
+                .iter()
+                .any(|installation| installation.managed_path == managed)
+            {
+                installations.push(Installation {
+                    launcher,
+                    game_path,
+                    managed_path: managed,
+                });
+            }
+        }
+    }
+
+    installations
+}
+
+/// The folders Hollow Knight may be installed in by the launchers that run it on Linux
+/// # Arguments
+/// * `base_dir` - The base directories of the current user
+/// * `steam_roots` - The folders Steam is installed in
+fn linux_candidates(base_dir: &BaseDirs, steam_roots: &[PathBuf]) -> Vec<(Launcher, PathBuf)> {
+    let home = base_dir.home_dir();
+    let config_dirs = |native: &str, flatpak: &str| {
+        [
+            base_dir.config_dir().join(native),
+            home.join(".var/app")
+                .join(flatpak)
+                .join("config")
+                .join(native),
+        ]
+    };
+    let mut candidates = vec![];
+
+    // The Linux installer from GOG puts the game files in a "game" folder
+    for path in ["GOG Games/Hollow Knight", "GOG Games/Hollow Knight/game"] {
+        candidates.push((Launcher::Gog, home.join(path)));
+    }
+
+    // Games added to Steam from outside of it get a prefix of their own
+    for library in steam_roots
+        .iter()
+        .flat_map(|root| steam::library_folders(root))
+    {
+        for prefix in sub_folders(library.join("steamapps").join("compatdata").as_path()) {
+            candidates.extend(prefix_candidates(Launcher::Proton, prefix.as_path()));
+        }
+    }
+
+    // Lutris keeps a YAML file for each game with its executable and Wine prefix
+    for games_dir in config_dirs("lutris", "net.lutris.Lutris")
+        .iter()
+        .map(|config| config.join("games"))
+        .chain([base_dir.data_dir().join("lutris").join("games")])
+    {
+        for config in files(games_dir.as_path(), "yml") {
+            let text = match fs::read_to_string(config.as_path()) {
+                Ok(text) => text,
+                Err(_) => continue,
+            };
+            let (game_path, prefix) = lutris_game(text.as_str());
+            if let Some(game_path) = game_path {
+                candidates.push((Launcher::Lutris, game_path));
+            }
+            if let Some(prefix) = prefix {
+                candidates.extend(prefix_candidates(Launcher::Lutris, prefix.as_path()));
+            }
+        }
+    }
+    candidates.push((Launcher::Lutris, home.join("Games/hollow-knight")));
+    candidates.extend(prefix_candidates(
+        Launcher::Lutris,
+        home.join("Games/hollow-knight").as_path(),
+    ));
+
+    // Heroic lists its installed GOG games and keeps a Wine prefix per game
+    for config in config_dirs("heroic", "com.heroicgameslauncher.hgl") {
+        if let Ok(text) = fs::read_to_string(config.join("gog_store").join("installed.json")) {
+            for install_path in heroic_install_paths(text.as_str()) {
+                candidates.push((Launcher::Heroic, install_path));
+            }
+        }
+    }
+    candidates.push((Launcher::Heroic, home.join("Games/Heroic/Hollow Knight")));
+    for prefix in sub_folders(home.join("Games/Heroic/Prefixes").as_path()) {
+        candidates.extend(prefix_candidates(Launcher::Heroic, prefix.as_path()));
+    }
+
+    candidates.extend(prefix_candidates(
+        Launcher::Wine,
+        home.join(".wine").as_path(),
+    ));
+
+    candidates
+}
+
+/// The folders Hollow Knight may be installed in inside of a Wine or Proton prefix
+/// # Arguments
+/// * `launcher` - The launcher the prefix belongs to
+/// * `prefix` - The prefix folder, which holds either `drive_c` or a `pfx` folder with it
+fn prefix_candidates(launcher: Launcher, prefix: &Path) -> Vec<(Launcher, PathBuf)> {
+    [prefix.join("drive_c"), prefix.join("pfx").join("drive_c")]
+        .into_iter()
+        .filter(|drive| drive.is_dir())
+        .flat_map(|drive| {
+            STATIC_PATHS
+                .iter()
+                .chain(PREFIX_PATHS.iter())
+                .map(|path| (launcher, drive.join(path)))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// The game folder and Wine prefix in a Lutris game config, from the `exe` and `prefix` keys of
+/// its `game` section
+/// # Arguments
+/// * `text` - The contents of the config
+fn lutris_game(text: &str) -> (Option<PathBuf>, Option<PathBuf>) {
+    let game_path = yaml_value(text, "game", "exe")
+        .and_then(|exe| Path::new(exe.as_str()).parent().map(Path::to_path_buf))
+        .filter(|game_path| !game_path.as_os_str().is_empty());
+    let prefix = yaml_value(text, "game", "prefix").map(PathBuf::from);
+    (game_path, prefix)
+}
+
+/// The install paths of the games listed in Heroic's `installed.json` for GOG
+/// # Arguments
+/// * `text` - The contents of the file
+fn heroic_install_paths(text: &str) -> Vec<PathBuf> {
+    serde_json::from_str::<serde_json::Value>(text)
+        .ok()
+        .as_ref()
+        .and_then(|installed| installed.get("installed"))
+        .and_then(|games| games.as_array())
+        .map(|games| {
+            games
+                .iter()
+                .filter_map(|game| game.get("install_path").and_then(|path| path.as_str()))
+                .map(PathBuf::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Get the value of a key directly inside of a top level section of a YAML file, without its
+/// quotes or a trailing comment; Nested keys and commented out lines are skipped
+/// # Arguments
+/// * `text` - The contents of the file
+/// * `section` - The top level key of the section
+/// * `key` - The key to look for in the section
+fn yaml_value(text: &str, section: &str, key: &str) -> Option<String> {
+    let mut in_section = false;
+    let mut section_indent = None;
+    for line in text.lines() {
+        let content = line.trim_start();
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - content.len();
+        if indent == 0 {
+            in_section = content.split_once(':').map_or(false, |(line_key, rest)| {
+                line_key.trim() == section && rest.trim().is_empty()
+            });
+            section_indent = None;
+            continue;
+        }
+        // The keys of a section are indented like its first line; Deeper lines are nested
+        if !in_section || indent != *section_indent.get_or_insert(indent) {
+            continue;
+        }
+
+        let (line_key, value) = match content.split_once(':') {
+            Some(pair) => pair,
+            None => continue,
+        };
+        if line_key.trim() != key {
+            continue;
+        }
+        let value = value.trim();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or_default(),
+            _ => value.split(" #").next().unwrap_or_default().trim(),
+        };
+        return (!value.is_empty()).then(|| value.to_string());
+    }
+
+    None
+}
+
+/// The folders directly inside of a folder, or nothing if it cannot be read
+/// # Arguments
+/// * `path` - The folder
+fn sub_folders(path: &Path) -> Vec<PathBuf> {
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The files with an extension directly inside of a folder, or nothing if it cannot be read
+/// # Arguments
+/// * `path` - The folder
+/// * `extension` - The extension of the files, without the dot
+fn files(path: &Path, extension: &str) -> Vec<PathBuf> {
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.is_file() && path.extension().map_or(false, |ext| ext == extension)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TestDir;
+
+    /// A Lutris config for the GOG release of the game installed with its Wine installer
+    const LUTRIS_CONFIG: &str = r#"game:
+  args: ''
+  exe: /home/knight/Games/hollow-knight/drive_c/GOG Games/Hollow Knight/hollow_knight.exe
+  prefix: "/home/knight/Games/hollow-knight"
+  working_dir: /home/knight/Games/hollow-knight/drive_c/GOG Games/Hollow Knight
+game_slug: hollow-knight
+name: Hollow Knight
+requires: null
+script:
+  files:
+  - installer: N/A:Select the setup file
+  game:
+    exe: drive_c/GOG Games/Hollow Knight/hollow_knight.exe
+    prefix: $GAMEDIR
+  installer:
+  - task:
+      name: create_prefix
+      prefix: $GAMEDIR
+slug: hollow-knight-gog
+system:
+  env:
+    DXVK_HUD: '0'
+version: GOG
+wine:
+  version: lutris-GE-Proton8-26-x86_64
+year: 2017
+"#;
+
+    /// Heroic's list of installed GOG games
+    const HEROIC_INSTALLED: &str = r#"{
+  "installed": [
+    {
+      "platform": "windows",
+      "executable": "",
+      "install_path": "/home/knight/Games/Heroic/Hollow Knight",
+      "install_size": "9.06 GiB",
+      "is_dlc": false,
+      "version": "1.5.78.11833",
+      "appName": "1308320804",
+      "installedWithDLCs": false,
+      "language": "en-US",
+      "versionEtag": "\"a1b2c3\"",
+      "buildId": "56243245768723470"
+    },
+    {
+      "platform": "linux",
+      "executable": "",
+      "install_path": "/home/knight/Games/Heroic/Celeste",
+      "install_size": "1.19 GiB",
+      "is_dlc": false,
+      "version": "1.4.0.0",
+      "appName": "1152315402",
+      "installedWithDLCs": false,
+      "language": "en-US",
+      "versionEtag": "",
+      "buildId": ""
+    }
+  ]
+}"#;
+
+    #[test]
+    fn reads_lutris_game_section() {
+        let (game_path, prefix) = lutris_game(LUTRIS_CONFIG);
+        assert_eq!(
+            game_path,
+            Some(PathBuf::from(
+                "/home/knight/Games/hollow-knight/drive_c/GOG Games/Hollow Knight"
+            ))
+        );
+        assert_eq!(
+            prefix,
+            Some(PathBuf::from("/home/knight/Games/hollow-knight"))
+        );
+    }
+
+    #[test]
+    fn yaml_value_skips_nested_and_commented_keys() {
+        let text = "script:\n  game:\n    exe: nested.exe\ngame:\n  # exe: commented.exe\n  \
+                    options:\n    exe: deeper.exe\n  exe: 'game.exe' # the launcher\n";
+        assert_eq!(
+            yaml_value(text, "game", "exe"),
+            Some("game.exe".to_string())
+        );
+        assert_eq!(yaml_value(text, "game", "prefix"), None);
+        assert_eq!(
+            yaml_value(
+                "game:\n  exe: /games/hk/hollow_knight # native\n",
+                "game",
+                "exe"
+            ),
+            Some("/games/hk/hollow_knight".to_string())
+        );
+        // Only the script section has a game section with an exe, which is not the config's
+        assert_eq!(
+            lutris_game("script:\n  game:\n    exe: a/b.exe\n"),
+            (None, None)
+        );
+    }
+
+    #[test]
+    fn reads_heroic_install_paths() {
+        assert_eq!(
+            heroic_install_paths(HEROIC_INSTALLED),
+            [
+                PathBuf::from("/home/knight/Games/Heroic/Hollow Knight"),
+                PathBuf::from("/home/knight/Games/Heroic/Celeste"),
+            ]
+        );
+        assert!(heroic_install_paths("{}").is_empty());
+        assert!(heroic_install_paths("not json").is_empty());
+    }
+
+    #[test]
+    fn finds_prefix_drives() {
+        let dir = TestDir::new("detect-prefix");
+        let wine = dir.0.join("wine");
+        let proton = dir.0.join("proton");
+        fs::create_dir_all(wine.join("drive_c")).unwrap();
+        fs::create_dir_all(proton.join("pfx").join("drive_c")).unwrap();
+
+        let candidates = prefix_candidates(Launcher::Wine, wine.as_path());
+        assert_eq!(candidates.len(), STATIC_PATHS.len() + PREFIX_PATHS.len());
+        assert!(candidates.contains(&(
+            Launcher::Wine,
+            wine.join("drive_c").join("GOG Games/Hollow Knight")
+        )));
+        assert!(prefix_candidates(Launcher::Proton, proton.as_path())
+            .iter()
+            .all(|(launcher, path)| *launcher == Launcher::Proton
+                && path.starts_with(proton.join("pfx").join("drive_c"))));
+        assert!(prefix_candidates(Launcher::Wine, dir.0.join("missing").as_path()).is_empty());
+    }
+
+    #[test]
+    fn finds_game_path_of_managed_folder() {
+        for suffix in SUFFIXES {
+            let managed = Path::new("/games/Hollow Knight").join(suffix);
+            assert_eq!(
+                game_path(managed.as_path()),
+                Some(PathBuf::from("/games/Hollow Knight"))
+            );
+        }
+        assert_eq!(game_path(Path::new("/games/Hollow Knight/Managed")), None);
+    }
+
+    #[test]
+    fn tells_platform_of_game_build() {
+        let dir = TestDir::new("detect-platform");
+        let windows = dir.0.join("windows");
+        let linux = dir.0.join("linux");
+        for (game_path, executable) in [
+            (windows.as_path(), "hollow_knight.exe"),
+            (linux.as_path(), "hollow_knight.x86_64"),
+        ] {
+            fs::create_dir_all(game_path.join("hollow_knight_Data/Managed")).unwrap();
+            fs::write(game_path.join(executable), "").unwrap();
+        }
+        assert_eq!(
+            platform(windows.join("hollow_knight_Data/Managed").as_path()),
+            Some(Platform::Windows)
+        );
+        assert_eq!(
+            platform(linux.join("hollow_knight_Data/Managed").as_path()),
+            Some(Platform::Linux)
+        );
+        assert_eq!(
+            platform(Path::new(
+                "/games/hollow_knight.app/Contents/Resources/Data/Managed"
+            )),
+            Some(Platform::Mac)
+        );
+    }
+}
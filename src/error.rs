@@ -0,0 +1,44 @@
+use crate::extract::ExtractError;
+use crate::mod_links::resolver::ResolveError;
+use std::fmt;
+
+/// Errors that can occur while downloading and installing a mod
+#[derive(Debug)]
+pub enum DownloadError {
+    /// The request for the mod could not be completed
+    Network(String),
+    /// The downloaded file could not be written, read or extracted
+    Io(String),
+    /// The downloaded file's SHA256 does not match the one listed in its manifest
+    HashMismatch {
+        name: String,
+        expected: String,
+        actual: String,
+    },
+    /// The mods to install could not be resolved against the mod list
+    Resolve(ResolveError),
+    /// The downloaded archive could not be extracted or contains unsafe paths
+    Extract(ExtractError),
+}
+
+impl fmt::Display for DownloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DownloadError::Network(e) => write!(f, "network error: {}", e),
+            DownloadError::Io(e) => write!(f, "file error: {}", e),
+            DownloadError::HashMismatch {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "SHA256 of {:?} does not match its manifest (expected {}, got {})",
+                name, expected, actual
+            ),
+            DownloadError::Resolve(e) => write!(f, "{}", e),
+            DownloadError::Extract(e) => write!(f, "failed to extract archive: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DownloadError {}
@@ -0,0 +1,336 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use zip::ZipArchive;
+
+/// The bits of a Unix mode that hold the file type, and their value for symbolic links
+const S_IFMT: u32 = 0o170000;
+const S_IFLNK: u32 = 0o120000;
+
+/// Problems found while extracting an archive
+#[derive(Debug)]
+pub enum ExtractError {
+    /// The archive could not be read or one of its files could not be written
+    Io(String),
+    /// An entry has an absolute path or a path that leads out of the destination
+    UnsafePath(String),
+    /// An entry is a symbolic link to somewhere outside of the destination
+    UnsafeSymlink { entry: String, target: String },
+}
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtractError::Io(e) => write!(f, "{}", e),
+            ExtractError::UnsafePath(entry) => write!(
+                f,
+                "archive entry {:?} would be extracted outside of its folder",
+                entry
+            ),
+            ExtractError::UnsafeSymlink { entry, target } => write!(
+                f,
+                "archive entry {:?} links to {:?}, outside of its folder",
+                entry, target
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for ExtractError {
+    fn from(e: io::Error) -> Self {
+        ExtractError::Io(e.to_string())
+    }
+}
+
+impl From<zip::result::ZipError> for ExtractError {
+    fn from(e: zip::result::ZipError) -> Self {
+        ExtractError::Io(e.to_string())
+    }
+}
+
+/// An archive entry that has been checked and can be written
+enum Entry {
+    Directory(PathBuf),
+    File(PathBuf),
+    Symlink(PathBuf, PathBuf),
+}
+
+/// Turn the path of an archive entry into a path relative to the destination; Returns None if
+/// it is absolute or has `..` components
+/// # Arguments
+/// * `name` - The path of the entry in the archive
+pub fn relative_path(name: &str) -> Option<PathBuf> {
+    // Zip paths use forward slashes, but some archivers on Windows write backslashes
+    let name = name.replace('\\', "/");
+    if name.starts_with('/') {
+        return None;
+    }
+
+    let mut path = PathBuf::new();
+    for component in Path::new(name.as_str()).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(path)
+}
+
+/// Extract a zip archive into a folder; Every entry is checked before anything is written, so
+/// an archive with an unsafe entry leaves the folder untouched; Returns the number of files
+/// extracted
+/// # Arguments
+/// * `archive_path` - The path to the zip archive
+/// * `destination` - The folder to extract the archive into
+pub fn extract_zip(archive_path: &Path, destination: &Path) -> Result<usize, ExtractError> {
+    let mut archive = ZipArchive::new(File::open(archive_path)?)?;
+
+    let mut entries = vec![];
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let name = file.name().to_string();
+        let path = match relative_path(name.as_str()) {
+            Some(path) => path,
+            None => return Err(ExtractError::UnsafePath(name)),
+        };
+        if path.as_os_str().is_empty() {
+            continue;
+        }
+
+        let is_symlink = file
+            .unix_mode()
+            .map_or(false, |mode| mode & S_IFMT == S_IFLNK);
+        if is_symlink {
+            let mut target = String::new();
+            file.read_to_string(&mut target)?;
+            // Links may only point further down into the folder; Without `..` components no
+            // chain of links can climb back out of it
+            match relative_path(target.as_str()) {
+                Some(target_path) if !target_path.as_os_str().is_empty() => {
+                    entries.push((i, Entry::Symlink(path, target_path)))
+                }
+                _ => {
+                    return Err(ExtractError::UnsafeSymlink {
+                        entry: name,
+                        target,
+                    })
+                }
+            }
+        } else if name.ends_with('/') || name.ends_with('\\') {
+            entries.push((i, Entry::Directory(path)));
+        } else {
+            entries.push((i, Entry::File(path)));
+        }
+    }
+
+    let mut files = 0;
+    for (i, entry) in entries {
+        match entry {
+            Entry::Directory(path) => fs::create_dir_all(destination.join(path))?,
+            Entry::File(path) => {
+                let out_path = destination.join(path);
+                if let Some(parent) = out_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                let mut out_file = File::create(out_path)?;
+                io::copy(&mut archive.by_index(i)?, &mut out_file)?;
+                files += 1;
+            }
+            Entry::Symlink(path, target) => {
+                let out_path = destination.join(path);
+                if let Some(parent) = out_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                create_symlink(target.as_path(), out_path.as_path())?;
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, path: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, path)
+}
+
+#[cfg(not(unix))]
+fn create_symlink(target: &Path, path: &Path) -> io::Result<()> {
+    log::warn!("Skipping symbolic link {:?} to {:?}", path, target);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TestDir;
+    use std::io::{Cursor, Write};
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    /// A test folder with an empty `out` folder to extract into
+    fn test_dir(name: &str) -> TestDir {
+        let dir = TestDir::new(format!("extract-{}", name).as_str());
+        fs::create_dir_all(out(&dir)).unwrap();
+        dir
+    }
+
+    fn out(dir: &TestDir) -> PathBuf {
+        dir.0.join("out")
+    }
+
+    /// Write a zip archive with the given (path, contents) entries; Entries whose path is in
+    /// `symlinks` are marked as symbolic links to their contents
+    fn write_zip(dir: &TestDir, entries: &[(&str, &str)], symlinks: &[&str]) -> PathBuf {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in entries {
+            writer.start_file(*name, FileOptions::default()).unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        let mut bytes = writer.finish().unwrap().into_inner();
+
+        // The writer cannot create links, so patch the file type into the central directory
+        let mut offset = 0;
+        while offset + 46 <= bytes.len() {
+            if bytes[offset..offset + 4] != [b'P', b'K', 1, 2] {
+                offset += 1;
+                continue;
+            }
+            let name_length = u16::from_le_bytes([bytes[offset + 28], bytes[offset + 29]]) as usize;
+            let name = String::from_utf8_lossy(&bytes[offset + 46..offset + 46 + name_length]);
+            if symlinks.contains(&name.as_ref()) {
+                let attributes = (S_IFLNK | 0o777) << 16;
+                bytes[offset + 38..offset + 42].copy_from_slice(&attributes.to_le_bytes());
+            }
+            offset += 46 + name_length;
+        }
+
+        let path = dir.0.join("archive.zip");
+        fs::write(path.as_path(), bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn extracts_nested_files() {
+        let dir = test_dir("nested");
+        let archive = write_zip(
+            &dir,
+            &[("Mod.dll", "dll"), ("./docs/README.md", "readme")],
+            &[],
+        );
+
+        assert_eq!(
+            extract_zip(archive.as_path(), out(&dir).as_path()).unwrap(),
+            2
+        );
+        assert_eq!(
+            fs::read_to_string(out(&dir).join("Mod.dll")).unwrap(),
+            "dll"
+        );
+        assert_eq!(
+            fs::read_to_string(out(&dir).join("docs").join("README.md")).unwrap(),
+            "readme"
+        );
+    }
+
+    #[test]
+    fn rejects_parent_dir_entries() {
+        let dir = test_dir("parent");
+        let archive = write_zip(&dir, &[("Mod.dll", "dll"), ("../evil.dll", "evil")], &[]);
+
+        let result = extract_zip(archive.as_path(), out(&dir).as_path());
+        assert!(matches!(result, Err(ExtractError::UnsafePath(_))));
+        assert!(!dir.0.join("evil.dll").exists());
+        // Nothing is written once an unsafe entry is found, even entries that came before it
+        assert!(!out(&dir).join("Mod.dll").exists());
+    }
+
+    #[test]
+    fn rejects_nested_parent_dir_entries() {
+        let dir = test_dir("nested-parent");
+        let archive = write_zip(&dir, &[("docs/../../evil.dll", "evil")], &[]);
+
+        let result = extract_zip(archive.as_path(), out(&dir).as_path());
+        assert!(matches!(result, Err(ExtractError::UnsafePath(_))));
+        assert!(!dir.0.join("evil.dll").exists());
+    }
+
+    #[test]
+    fn rejects_backslash_parent_dir_entries() {
+        let dir = test_dir("backslash");
+        let archive = write_zip(&dir, &[("..\\evil.dll", "evil")], &[]);
+
+        let result = extract_zip(archive.as_path(), out(&dir).as_path());
+        assert!(matches!(result, Err(ExtractError::UnsafePath(_))));
+    }
+
+    #[test]
+    fn rejects_absolute_entries() {
+        let dir = test_dir("absolute");
+        let target = dir.0.join("evil.dll");
+        let archive = write_zip(&dir, &[(target.to_str().unwrap(), "evil")], &[]);
+
+        let result = extract_zip(archive.as_path(), out(&dir).as_path());
+        assert!(matches!(result, Err(ExtractError::UnsafePath(_))));
+        assert!(!target.exists());
+    }
+
+    #[test]
+    fn relative_path_rejects_escaping_names() {
+        assert_eq!(
+            relative_path("./hollow_knight_Data\\Managed.dll"),
+            Some(PathBuf::from("hollow_knight_Data").join("Managed.dll"))
+        );
+        for name in [
+            "../Assembly-CSharp.dll",
+            "/etc/passwd",
+            "a/../../b",
+            "..\\b",
+        ] {
+            assert_eq!(relative_path(name), None, "{:?} was not rejected", name);
+        }
+    }
+
+    #[test]
+    fn rejects_escaping_symlinks() {
+        let dir = test_dir("symlink-escape");
+        for target in ["../outside", "/etc", "docs/../.."] {
+            let archive = write_zip(&dir, &[("link", target)], &["link"]);
+
+            let result = extract_zip(archive.as_path(), out(&dir).as_path());
+            assert!(
+                matches!(result, Err(ExtractError::UnsafeSymlink { .. })),
+                "link to {:?} was not rejected",
+                target
+            );
+            assert!(fs::symlink_metadata(out(&dir).join("link")).is_err());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn extracts_symlinks_inside_destination() {
+        let dir = test_dir("symlink-inside");
+        let archive = write_zip(
+            &dir,
+            &[
+                ("docs/README.md", "readme"),
+                ("README.md", "docs/README.md"),
+            ],
+            &["README.md"],
+        );
+
+        assert_eq!(
+            extract_zip(archive.as_path(), out(&dir).as_path()).unwrap(),
+            1
+        );
+        let link = out(&dir).join("README.md");
+        assert!(fs::symlink_metadata(link.as_path())
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_to_string(link).unwrap(), "readme");
+    }
+}
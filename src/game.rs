@@ -0,0 +1,246 @@
+use std::fs;
+use std::path::Path;
+
+/// The Unity files in the data folder that hold the player settings, newest layout first
+const SETTINGS_FILES: [&str; 2] = ["globalgamemanagers", "mainData"];
+
+/// Whether a string looks like a Hollow Knight version, such as `1.5.78.11833` or `1.4.3.2`
+/// # Arguments
+/// * `text` - The string to check
+fn is_game_version(text: &str) -> bool {
+    let parts: Vec<&str> = text.split('.').collect();
+    parts.len() == 4
+        && parts.iter().all(|part| {
+            !part.is_empty() && part.len() <= 6 && part.chars().all(|c| c.is_ascii_digit())
+        })
+}
+
+/// Find the first game version stored as a length prefixed string, the way Unity serializes
+/// the bundle version in its player settings
+/// # Arguments
+/// * `bytes` - The contents of a Unity serialized file
+fn find_serialized_version(bytes: &[u8]) -> Option<String> {
+    let is_version_byte = |byte: &u8| byte.is_ascii_digit() || *byte == b'.';
+    let mut start = 0;
+    while start < bytes.len() {
+        if !is_version_byte(&bytes[start]) {
+            start += 1;
+            continue;
+        }
+        let end = start
+            + bytes[start..]
+                .iter()
+                .take_while(|byte| is_version_byte(byte))
+                .count();
+        let length = (end - start) as u32;
+        if start >= 4 && bytes[start - 4..start] == length.to_le_bytes() {
+            let text = String::from_utf8_lossy(&bytes[start..end]);
+            if is_game_version(text.as_ref()) {
+                return Some(text.to_string());
+            }
+        }
+        start = end;
+    }
+
+    None
+}
+
+/// Find the first game version stored as a UTF-16 string, the way .NET stores string literals
+/// such as the version constant in Assembly-CSharp.dll
+/// # Arguments
+/// * `bytes` - The contents of an assembly
+fn find_utf16_version(bytes: &[u8]) -> Option<String> {
+    for offset in 0..2 {
+        let mut text = String::new();
+        for pair in bytes.get(offset..).unwrap_or_default().chunks_exact(2) {
+            match (pair[0], pair[1]) {
+                (c, 0) if c.is_ascii_digit() || c == b'.' => text.push(c as char),
+                _ => {
+                    if is_game_version(text.as_str()) {
+                        return Some(text);
+                    }
+                    text.clear();
+                }
+            }
+        }
+        // A version can also end the file
+        if is_game_version(text.as_str()) {
+            return Some(text);
+        }
+    }
+
+    None
+}
+
+/// Read the version of Hollow Knight that a game folder holds from its Unity player settings, or
+/// from the unmodded Assembly-CSharp.dll if they cannot be read
+/// # Arguments
+/// * `managed_path` - The path to the game's Managed folder
+pub fn game_version(managed_path: &Path) -> Option<String> {
+    let data_path = managed_path.parent()?;
+    for file in SETTINGS_FILES {
+        if let Ok(bytes) = fs::read(data_path.join(file)) {
+            if let Some(version) = find_serialized_version(bytes.as_slice()) {
+                return Some(version);
+            }
+        }
+    }
+
+    // The Modding API replaces Assembly-CSharp.dll, so prefer the vanilla backup
+    ["Assembly-CSharp.dll.vanilla", "Assembly-CSharp.dll"]
+        .iter()
+        .filter_map(|file| fs::read(managed_path.join(file)).ok())
+        .find_map(|bytes| find_utf16_version(bytes.as_slice()))
+}
+
+/// The version of Hollow Knight that a Modding API release targets, read from the tag of the
+/// GitHub release it is downloaded from, such as `1.5.78.11833` for
+/// `.../releases/download/1.5.78.11833-77/ModdingApiWin.zip`; ApiLinks only gives the API's own
+/// build number as its version
+/// # Arguments
+/// * `api_url` - The download link of the Modding API release
+pub fn api_game_version(api_url: &str) -> Option<&str> {
+    let (_, rest) = api_url.split_once("/releases/download/")?;
+    let tag = rest.split('/').next()?;
+    let game_version = tag
+        .split_once('-')
+        .map_or(tag, |(game_version, _)| game_version);
+    if is_game_version(game_version) {
+        Some(game_version)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const API_LINKS: &str = r#"<?xml version="1.0"?>
+<ApiLinks xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:xsd="http://www.w3.org/2001/XMLSchema" xmlns="https://github.com/HollowKnight-Modding/HollowKnight.ModLinks/HollowKnight.ModManager">
+  <Manifest>
+    <Version>77</Version>
+    <Links>
+      <Linux SHA256="0a6a4c1a0e6ab1ab0ad2bcb6f6c3e0a4ed3bd7bb8a6b0e1b8b4d6c2c7d0b7c0e">
+        <![CDATA[https://github.com/hk-modding/api/releases/download/1.5.78.11833-77/ModdingApiLinux.zip]]>
+      </Linux>
+      <Mac SHA256="1b7b5d2b1f7bc2bc1be3cdc7f7d4f1b5fe4ce8cc9b7c1f2c9c5e7d3d8e1c8d1f">
+        <![CDATA[https://github.com/hk-modding/api/releases/download/1.5.78.11833-77/ModdingApiMac.zip]]>
+      </Mac>
+      <Windows SHA256="2c8c6e3c2a8cd3cd2cf4ded8a8e5a2c6af5df9dd0c8d2a3d0d6f8e4e9f2d9e2a">
+        <![CDATA[https://github.com/hk-modding/api/releases/download/1.5.78.11833-77/ModdingApiWin.zip]]>
+      </Windows>
+    </Links>
+    <Files>
+      <File>Assembly-CSharp.dll</File>
+      <File>MMHOOK_Assembly-CSharp.dll</File>
+      <File>MMHOOK_PlayMaker.dll</File>
+    </Files>
+  </Manifest>
+</ApiLinks>"#;
+
+    /// Encode text as UTF-16LE, the way .NET stores string literals
+    fn utf16(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    #[test]
+    fn recognizes_game_versions() {
+        assert!(is_game_version("1.5.78.11833"));
+        assert!(is_game_version("1.4.3.2"));
+        for text in [
+            "",
+            "1.5.78",
+            "1.5.78.11833.1",
+            "1..78.11833",
+            "1.5.78.1234567",
+            "1.5.a.2",
+        ] {
+            assert!(!is_game_version(text), "{:?} was accepted", text);
+        }
+    }
+
+    #[test]
+    fn finds_serialized_version() {
+        let mut bytes = b"\x00\x05\x00\x00\x001.2.3junk".to_vec();
+        // Not length prefixed, so not the bundle version
+        bytes.extend_from_slice(b"9.9.9.9\x00");
+        bytes.extend_from_slice(&12u32.to_le_bytes());
+        bytes.extend_from_slice(b"1.5.78.11833");
+        bytes.extend_from_slice(&[0, 0, 0]);
+        assert_eq!(
+            find_serialized_version(bytes.as_slice()),
+            Some("1.5.78.11833".to_string())
+        );
+
+        // A length that does not match the digits that follow is skipped
+        let mut bytes = 11u32.to_le_bytes().to_vec();
+        bytes.extend_from_slice(b"1.5.78.11833");
+        assert_eq!(find_serialized_version(bytes.as_slice()), None);
+        assert_eq!(find_serialized_version(&[]), None);
+    }
+
+    #[test]
+    fn finds_utf16_version() {
+        let mut bytes = utf16("Version 1.2");
+        bytes.extend(utf16("\u{0}1.5.78.11833\u{0}"));
+        assert_eq!(
+            find_utf16_version(bytes.as_slice()),
+            Some("1.5.78.11833".to_string())
+        );
+
+        // At an odd offset
+        let mut bytes = vec![0xff];
+        bytes.extend(utf16("x1.4.3.2x"));
+        assert_eq!(
+            find_utf16_version(bytes.as_slice()),
+            Some("1.4.3.2".to_string())
+        );
+    }
+
+    #[test]
+    fn finds_utf16_version_at_end_of_file() {
+        assert_eq!(
+            find_utf16_version(utf16("1.5.78.11833").as_slice()),
+            Some("1.5.78.11833".to_string())
+        );
+    }
+
+    #[test]
+    fn handles_empty_and_truncated_assemblies() {
+        assert_eq!(find_utf16_version(&[]), None);
+        assert_eq!(find_utf16_version(b"1"), None);
+        assert_eq!(find_utf16_version(&[b'1', 0, b'.']), None);
+    }
+
+    #[test]
+    fn api_game_version_reads_release_tag() {
+        let api_links: crate::mod_links::api::ApiLinks =
+            quick_xml::de::from_str(API_LINKS).unwrap();
+        let manifest = api_links.manifest;
+        assert_eq!(manifest.version, "77");
+        for link in [
+            &manifest.links.linux,
+            &manifest.links.mac,
+            &manifest.links.windows,
+        ] {
+            assert_eq!(api_game_version(link.link.trim()), Some("1.5.78.11833"));
+        }
+    }
+
+    #[test]
+    fn api_game_version_ignores_unknown_urls() {
+        assert_eq!(api_game_version("77"), None);
+        assert_eq!(api_game_version("file:///tmp/ModdingApiWin.zip"), None);
+        assert_eq!(
+            api_game_version("https://github.com/hk-modding/api/releases/download/v77/api.zip"),
+            None
+        );
+        assert_eq!(
+            api_game_version(
+                "https://github.com/hk-modding/api/releases/download/1.4.3.2-63/api.zip"
+            ),
+            Some("1.4.3.2")
+        );
+    }
+}
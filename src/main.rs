@@ -1,32 +1,46 @@
 mod app;
+mod detect;
+mod error;
+mod extract;
+mod game;
 mod mod_links;
+mod steam;
+#[cfg(test)]
+mod test_util;
 
-use app::app::App;
-use app::args::{Arguments, SubCommand};
-use app::profile::Profile;
+use app::app::{App, DownloadOptions};
+use app::args::{Arguments, CacheCommand, InstallDirCommand, SubCommand};
+use app::install_dir::{InstallDir, DEFAULT_INSTALL_DIR};
+use app::installed::{InstalledFile, InstalledMod};
+use app::lock::{LockFile, LockedApi, LockedApiLink, LockedMod, LOCKFILE_VERSION};
+use app::profile::{Profile, ProfileMod};
 use clap::Parser;
+use detect::{Installation, Platform};
 use directories::BaseDirs;
+use error::DownloadError;
+use extract::ExtractError;
 use futures_util::StreamExt;
+use is_terminal::IsTerminal;
 use log::{error, info, warn, LevelFilter};
 use mod_links::api::*;
 use mod_links::local::*;
 use mod_links::remote::*;
-use reqwest;
-use serde_json;
+use mod_links::resolver;
+use mod_links::version::{self, Constraint};
+use serde::de::DeserializeOwned;
 use sha256::digest_file;
-use simple_logging;
 use std::cmp::min;
-use std::env;
+use std::collections::HashMap;
 use std::fs;
-use std::fs::File;
-use std::io::{self, Cursor, Write};
-use std::path::PathBuf;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
 use std::str::FromStr;
-use std::sync::{mpsc, Mutex, MutexGuard};
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use sysinfo::{ProcessExt, System, SystemExt};
-use tokio;
 use tokio::runtime::Runtime;
-use unzip::Unzipper;
 
 /// An array of possible paths to the folder containing the Hollow Knight executable
 static STATIC_PATHS: [&str; 6] = [
@@ -50,170 +64,184 @@ static SUFFIXES: [&str; 3] = [
 
 struct AppState(Mutex<App>);
 
-const API_URL: &str = "https://raw.githubusercontent.com/hk-modding/modlinks/main/ApiLinks.xml";
-const MOD_URL: &str = "https://raw.githubusercontent.com/hk-modding/modlinks/main/ModLinks.xml";
+/// The name of the mod source for the official ModLinks
+const OFFICIAL_SOURCE: &str = "modlinks";
 const SETTINGS_FOLDER: &str = "hkdl";
+/// The number of times a mod is downloaded before giving up on a hash mismatch
+const VERIFY_ATTEMPTS: u8 = 3;
+/// The longest a failed download waits before it is retried
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+/// The folder inside the mods folder that mods are extracted into before being moved into place
+const STAGING_FOLDER: &str = ".hkdl-staging";
+/// The folder inside the mods folder that replaced mods are kept in until an install plan succeeds
+const BACKUP_FOLDER: &str = ".hkdl-backup";
+/// The folder in the settings folder that verified downloads are kept in, by SHA256
+const CACHE_FOLDER: &str = "Cache";
+/// The folder in the settings folder that the Modding API is extracted into before being moved
+/// into place
+const API_STAGING_FOLDER: &str = "ApiStaging";
+
+/// Messages sent by a running download to whatever reports on it
+enum DownloadEvent {
+    /// The percentage of the file that has been downloaded
+    Progress(u8),
+    /// The number of bytes downloaded so far, for files whose size the server did not send
+    Received(u64),
+    /// The download ended, with the path to the downloaded file or the reason it failed
+    Finished(Result<PathBuf, DownloadError>),
+}
+
+/// The kinds of files a mod can be downloaded as, told apart by their first bytes
+enum ArtifactKind {
+    Zip,
+    Dll,
+}
+
+/// What installing a single mod of an install plan changed, so that it can be undone
+enum InstallStep {
+    /// The mod was already installed and enabled
+    Unchanged,
+    /// The mod was installed but disabled and has been enabled
+    Enabled,
+    /// The mod was downloaded and installed
+    Installed,
+    /// Another version of the mod was moved aside and the mod was downloaded and installed
+    Replaced(Backup),
+}
+
+/// A mod folder moved out of the way of another version, with what is needed to put it back
+struct Backup {
+    /// Where the folder was moved from
+    original: PathBuf,
+    /// Where the folder was moved to
+    path: PathBuf,
+    /// Whether the mod was enabled
+    enabled: bool,
+    /// The install record of the mod, if it had one
+    record: Option<InstalledMod>,
+}
 
 fn main() {
+    let args = Arguments::parse();
     let state = AppState(Default::default());
+    {
+        let mut app_state = state.0.lock().unwrap();
+        app_state.download_options.skip_verify = args.skip_verify;
+        if let Some(retries) = args.retries {
+            app_state.download_options.retries = retries;
+        }
+        if let Some(timeout) = args.timeout {
+            app_state.download_options.timeout = Duration::from_secs(timeout);
+        }
+        app_state.offline = args.offline;
+        app_state.non_interactive = args.yes;
+        if let Some(jobs) = args.jobs {
+            app_state.pool.set_num_threads(jobs as usize);
+        }
+    }
     exit_game();
     check_settings(&state);
-    auto_detect(&state);
+    if let Err(e) = select_install_dir(args.install.clone(), &state) {
+        eprintln!("Error: {}", e);
+        error!("Failed to select installation: {}", e);
+        process::exit(1);
+    }
+    set_links_urls(&args, &state);
+    if let Some(game_path) = &args.game_path {
+        if let Err(e) = set_game_path(&mut state.0.lock().unwrap(), game_path.as_str()) {
+            eprintln!("Error: {}", e);
+            error!("Failed to set game path: {}", e);
+            process::exit(1);
+        }
+    }
+    // Installations are managed by path, so there is no need to look for the game first
+    if !matches!(args.cmd, SubCommand::InstallDirs { .. }) {
+        if let Err(e) = auto_detect(&state) {
+            eprintln!("Error: {}", e);
+            error!("Failed to find the game: {}", e);
+            process::exit(1);
+        }
+    }
     fetch_mod_list(&state);
-    parse_args(&state);
+    let exit_code = parse_args(args, &state);
     exit_app(&state);
+    process::exit(exit_code);
 }
 
 /// Automatically detect the path to Hollow Knight executable, else prompt the user to select its path.
 /// # Arguments
 /// * `state` - The state of the application
-fn auto_detect(state: &AppState) {
-    {
+fn auto_detect(state: &AppState) -> Result<(), String> {
+    let non_interactive = {
         let state = state.0.lock().unwrap();
-        if state.settings.mods_path != "".to_string() {
-            return;
+        if !state.settings.mods_path.is_empty() {
+            return Ok(());
         }
-    }
+        state.non_interactive
+    };
 
-    match env::consts::OS {
-        "linux" | "mac" => {
-            let mut state = state.0.lock().unwrap();
-            match STATIC_PATHS.into_iter().find(|path| {
-                let base_dir = BaseDirs::new().unwrap();
-                let path_buf: PathBuf = [base_dir.data_dir().to_str().unwrap(), path]
-                    .iter()
-                    .collect();
-                path_buf.exists()
-            }) {
-                Some(game_path) => {
-                    let mut input = "".to_string();
-                    print!(
-                        "Game path detected at: {}. Is this correct? [y/n] ",
-                        game_path
-                    );
-                    io::stdout().flush().unwrap();
-                    io::stdin()
-                        .read_line(&mut input)
-                        .expect("Error: unable to read user input.");
-                    while input.trim().to_lowercase() != "y" && input.trim().to_lowercase() != "n" {
-                        println!("This is not a valid input. Please enter 'y' for 'yes' or 'n' for 'no'.");
-                        print!(
-                            "Game path detected at: {}. Is this correct? [y/n] ",
-                            game_path
-                        );
-                        io::stdout().flush().unwrap();
-                        io::stdin()
-                            .read_line(&mut input)
-                            .expect("Error: unable to read user input.");
-                    }
-                    if input.trim() == "y" {
-                        match SUFFIXES.into_iter().find(|suffix| {
-                            let path_buf: PathBuf = [game_path, suffix].iter().collect();
-                            path_buf.exists()
-                        }) {
-                            Some(suffix) => {
-                                let base_dir = BaseDirs::new().unwrap();
-                                state.settings.mods_path = format!(
-                                    "{}/{}/{}/Mods",
-                                    base_dir.data_dir().to_str().unwrap(),
-                                    game_path,
-                                    suffix
-                                )
-                                .to_string();
-                            }
-                            None => {
-                                error!("No managed path exists.");
-                            }
-                        }
-                    } else {
-                    }
-                }
-                None => {
-                    println!("Could not detect your Hollow Knight installation. Please enter the folder that contains your Hollow Knight executable.");
-                    enter_game_path(state)
-                }
+    let installations = detect::installations();
+    let installation = match installations.as_slice() {
+        [] => {
+            if !non_interactive {
+                println!("Could not detect your Hollow Knight installation. Please enter the folder that contains your Hollow Knight executable.");
             }
+            None
         }
-        "windows" => {
-            let mut state = state.0.lock().unwrap();
-            let mut drive_letter: String = "C:/".to_string();
-            for i in 65u8..=90 {
-                if PathBuf::from_str(format!("{}:/", i).as_str())
-                    .unwrap()
-                    .exists()
-                {
-                    drive_letter = format!("{}:/", i);
-                }
-            }
-            match STATIC_PATHS.into_iter().find(|path| {
-                let path_buf: PathBuf = [drive_letter.to_string(), path.to_string()]
-                    .iter()
-                    .collect();
-                info!(
-                    "Checking if path {} exists",
-                    path_buf.clone().into_os_string().into_string().unwrap()
-                );
-                path_buf.exists()
-            }) {
-                Some(game_path) => {
-                    let mut input = "".to_string();
-                    print!(
-                        "Game path detected at: {}. Is this correct? [y/n] ",
-                        game_path
-                    );
-                    io::stdout().flush().unwrap();
-                    io::stdin()
-                        .read_line(&mut input)
-                        .expect("Error: unable to read user input.");
-                    while input.trim().to_lowercase() != "y" && input.trim().to_lowercase() != "n" {
-                        println!("This is not a valid input. Please enter 'y' for 'yes' or 'n' for 'no'.");
-                        print!(
-                            "Game path detected at: {}. Is this correct? [y/n] ",
-                            game_path
-                        );
-                        io::stdout().flush().unwrap();
-                        io::stdin()
-                            .read_line(&mut input)
-                            .expect("Error: unable to read user input.");
-                    }
-                    if input.trim().to_lowercase() == "y" {
-                        match SUFFIXES.into_iter().find(|suffix| {
-                            let path_buf: PathBuf =
-                                [drive_letter.as_str(), game_path, suffix].iter().collect();
-                            info!(
-                                "Checking managed path: {}",
-                                path_buf.clone().into_os_string().into_string().unwrap()
-                            );
-                            path_buf.exists()
-                        }) {
-                            Some(suffix) => {
-                                state.settings.mods_path = format!(
-                                    "{}{}/{}/Mods",
-                                    drive_letter.as_str(),
-                                    game_path,
-                                    suffix
-                                );
-                            }
-                            None => error!("No managed path exists."),
-                        }
-                    } else if input.trim().to_lowercase() == "n" {
-                        enter_game_path(state);
-                    }
-                }
-                None => enter_game_path(state),
-            }
+        [installation] => {
+            let question = format!(
+                "Game path detected at: {} ({}). Is this correct?",
+                installation.game_path.display(),
+                installation.launcher
+            );
+            confirm(question.as_str(), non_interactive).then_some(installation)
         }
-        _ => panic!("OS not supported"),
+        installations => choose_installation(installations, non_interactive)?,
+    };
+    let mut app_state = state.0.lock().unwrap();
+    match installation {
+        Some(installation) => {
+            set_game_path(&mut app_state, installation.game_path.to_str().unwrap())
+        }
+        None => enter_game_path(&mut app_state),
     }
+}
 
-    {
-        let state = state.0.lock().unwrap();
-        let mods_path = &state.settings.mods_path;
-        if !PathBuf::from_str(mods_path.as_str()).unwrap().exists() {
-            match fs::create_dir(mods_path.as_str()) {
-                Ok(_) => info!("Successfully created mods directory."),
-                Err(e) => error!("Error creating mods folder: {}", e),
+/// Ask the user which of several detected installations to use; Returns None if they leave the
+/// answer blank
+/// # Arguments
+/// * `installations` - The detected installations
+/// * `non_interactive` - Whether to fail instead of asking
+fn choose_installation(
+    installations: &[Installation],
+    non_interactive: bool,
+) -> Result<Option<&Installation>, String> {
+    println!("Found {} Hollow Knight installations:", installations.len());
+    for (i, installation) in installations.iter().enumerate() {
+        println!(
+            "  {}. {} ({})",
+            i + 1,
+            installation.game_path.display(),
+            installation.launcher
+        );
+    }
+    loop {
+        let input = prompt(
+            "Enter the number of the installation to use, or leave blank to enter another path: ",
+            "several installations were found; pass the one to use with --game-path",
+            non_interactive,
+        )?;
+        if input.is_empty() {
+            return Ok(None);
+        }
+        match input.parse::<usize>() {
+            Ok(number) if number >= 1 && number <= installations.len() => {
+                return Ok(installations.get(number - 1))
             }
+            _ => println!(
+                "This is not a valid input. Please enter a number from 1 to {}.",
+                installations.len()
+            ),
         }
     }
 }
@@ -254,129 +282,150 @@ fn check_settings(state: &AppState) {
 
 /// Create a new profile and save it to settings
 /// # Arguments
+/// * `profile_name` - The name of the profile, or None to ask for it
+/// * `mods` - The mods to put in the profile, or None to ask for them
 /// * `state` - The state of the application
-fn create_profile(state: &AppState) {
+fn create_profile(
+    profile_name: Option<String>,
+    mods: Option<Vec<String>>,
+    state: &AppState,
+) -> Result<(), String> {
     let mut app_state = state.0.lock().unwrap();
-
-    let mut profile_name = "".to_string();
-    let mut mod_names = Vec::new();
-
-    print!("Enter the name of the new profile: ");
-    io::stdout().flush().unwrap();
-    io::stdin().read_line(&mut profile_name).unwrap();
-    let mut profile_exists = false;
-    for profile in app_state.settings.profiles.iter() {
-        if profile.name == profile_name {
-            profile_exists = true;
-        }
-    }
-    while profile_name == "".to_string() || profile_exists {
-        if profile_name == "".to_string() {
-            println!("Profile name cannot be empty.");
-        } else if profile_exists {
-            println!("Profile {:?} already exists.", profile_name);
+    let non_interactive = app_state.non_interactive;
+    let manifests = app_state.settings.mod_links.manifests.clone();
+    let check_name = |name: &str| {
+        if name.is_empty() {
+            Err("profile name cannot be empty".to_string())
+        } else if app_state
+            .settings
+            .profiles
+            .iter()
+            .any(|profile| profile.name == name)
+        {
+            Err(format!("profile {:?} already exists", name))
+        } else {
+            Ok(())
         }
+    };
 
-        print!("Enter the name of the new profile: ");
-        io::stdout().flush().unwrap();
-        io::stdin().read_line(&mut profile_name).unwrap();
-
-        profile_exists = false;
-        for profile in app_state.settings.profiles.iter() {
-            if profile.name == profile_name {
-                profile_exists = true;
-            }
+    let profile_name = match profile_name {
+        Some(profile_name) => {
+            let profile_name = profile_name.trim().to_string();
+            check_name(profile_name.as_str())?;
+            profile_name
         }
-    }
-
-    profile_name = profile_name.trim().to_string();
+        None => loop {
+            let profile_name = prompt(
+                "Enter the name of the new profile: ",
+                "no profile name was given; pass it with --profile-name",
+                non_interactive,
+            )?;
+            match check_name(profile_name.as_str()) {
+                Ok(_) => break profile_name,
+                Err(e) => println!("{}", e),
+            }
+        },
+    };
 
-    print!("Enter the name of a mod to include in the profile (leave blank to finish): ");
-    io::stdout().flush().unwrap();
-    let mut mod_name = "".to_string();
-    io::stdin().read_line(&mut mod_name).unwrap();
-    mod_name = mod_name.trim().replace(" ", "").to_lowercase();
-    let mut manifest_name = "".to_string();
-    while mod_name != "".to_string() {
-        let mut mod_exists = app_state
-            .settings
-            .mod_links
-            .manifests
-            .iter()
-            .any(|manifest| {
-                manifest_name = manifest.name.clone();
-                manifest.name.replace(" ", "").to_lowercase() == mod_name
-            });
-        while !mod_exists && mod_name != "".to_string() {
-            println!("Mod {:?} does not exist.", mod_name);
-            print!("Enter the name of a mod to include in the profile (leave blank to finish): ");
-            io::stdout().flush().unwrap();
-            mod_name = "".to_string();
-            io::stdin().read_line(&mut mod_name).unwrap();
-            mod_name = mod_name.trim().replace(" ", "").to_lowercase();
-            mod_exists = app_state
-                .settings
-                .mod_links
-                .manifests
-                .iter()
-                .any(|manifest| {
-                    manifest_name = manifest.name.clone();
-                    manifest.name.replace(" ", "").to_lowercase() == mod_name
-                });
+    let mut mod_names = Vec::new();
+    match mods {
+        Some(mods) => {
+            for query in mods.iter().map(|query| query.trim()) {
+                if query.is_empty() {
+                    continue;
+                }
+                match resolver::find(query, &manifests) {
+                    Some(manifest) => mod_names.push(manifest.name.clone()),
+                    None => return Err(format!("mod {:?} does not exist", query)),
+                }
+            }
         }
-        mod_names.push(manifest_name.clone());
-        print!("Enter the name of a mod to include in the profile (leave blank to finish): ");
-        io::stdout().flush().unwrap();
-        mod_name = "".to_string();
-        io::stdin().read_line(&mut mod_name).unwrap();
-        mod_name = mod_name.trim().replace(" ", "").to_lowercase();
+        None => loop {
+            let query = prompt(
+                "Enter the name of a mod to include in the profile (leave blank to finish): ",
+                "no mods were given for the profile; pass them with --mods",
+                non_interactive,
+            )?;
+            if query.is_empty() {
+                break;
+            }
+            match resolver::find(query.as_str(), &manifests) {
+                Some(manifest) => mod_names.push(manifest.name.clone()),
+                None => println!("Mod {:?} does not exist.", query),
+            }
+        },
     }
 
     app_state.settings.profiles.push(Profile {
-        name: profile_name,
-        mods: mod_names,
+        name: profile_name.clone(),
+        mods: mod_names.into_iter().map(ProfileMod::new).collect(),
     });
+    print_and_log(format!("Created profile {:?}.", profile_name));
+
+    Ok(())
 }
 
 /// Delete a profile from settings
 /// # Arguments
+/// * `profile_name` - The name of the profile, or None to ask for it
 /// * `state` - The state of the application
-fn delete_profile(state: &AppState) {
+fn delete_profile(profile_name: Option<String>, state: &AppState) -> Result<(), String> {
     let mut app_state = state.0.lock().unwrap();
-    if app_state.settings.profiles.len() == 0 {
+    if app_state.settings.profiles.is_empty() {
         println!("No profiles to delete.");
-        return;
+        return Ok(());
     }
 
-    print!("Enter the name of the profile to delete: ");
-    io::stdout().flush().unwrap();
-    let mut profile_name = "".to_string();
-    io::stdin().read_line(&mut profile_name).unwrap();
-    profile_name = profile_name.trim().to_string();
-    let mut profile_exists = app_state
+    let profile_name = existing_profile_name(
+        profile_name,
+        "Enter the name of the profile to delete: ",
+        &app_state,
+    )?;
+    app_state
         .settings
         .profiles
-        .iter()
-        .any(|profile| profile.name == profile_name);
-    while !profile_exists {
-        println!("Profile {:?} does not exist.", profile_name);
-        profile_name = "".to_string();
-        print!("Enter the name of the profile to delete: ");
-        io::stdout().flush().unwrap();
-        io::stdin().read_line(&mut profile_name).unwrap();
-        profile_name = profile_name.trim().to_string();
-        profile_exists = app_state
-            .settings
+        .retain(|p| p.name != profile_name);
+
+    println!("Profile {:?} deleted.", profile_name);
+    Ok(())
+}
+
+/// Get the name of an existing profile from the one that was passed, or ask for it until the
+/// user enters one that exists
+/// # Arguments
+/// * `profile_name` - The name of the profile, or None to ask for it
+/// * `question` - The prompt to ask for the name with
+/// * `app` - The application
+fn existing_profile_name(
+    profile_name: Option<String>,
+    question: &str,
+    app: &App,
+) -> Result<String, String> {
+    let exists = |name: &str| {
+        app.settings
             .profiles
             .iter()
-            .any(|profile| profile.name == profile_name);
+            .any(|profile| profile.name == name)
+    };
+    if let Some(profile_name) = profile_name {
+        let profile_name = profile_name.trim().to_string();
+        return match exists(profile_name.as_str()) {
+            true => Ok(profile_name),
+            false => Err(format!("profile {:?} does not exist", profile_name)),
+        };
     }
-    (*app_state)
-        .settings
-        .profiles
-        .retain(|p| p.name != profile_name);
 
-    println!("Profile {:?} deleted.", profile_name);
+    loop {
+        let profile_name = prompt(
+            question,
+            "no profile name was given; pass it with --profile-name",
+            app.non_interactive,
+        )?;
+        if exists(profile_name.as_str()) {
+            return Ok(profile_name);
+        }
+        println!("Profile {:?} does not exist.", profile_name);
+    }
 }
 
 /// Disables the Modding API
@@ -468,550 +517,2343 @@ fn disable_mod(mod_name: String, state: &AppState) {
     }
 }
 
-/// Download a mod to disk from a provided URL
+/// Download a mod from a provided URL into the downloads folder and verify it against its
+/// SHA256 in ModLinks; Returns the path to the downloaded file
 /// # Arguments
 /// * `tx` - The channel to send the download progress to
 /// * `name` - The name of the mod to be downloaded
 /// * `url` - The download link of the mod
-/// * `mods_path` - The path to the mods folder
-async fn download_mod(tx: mpsc::Sender<u8>, name: String, url: String, mods_path: String) {
-    let client = reqwest::Client::new();
-    let result = client
-        .get(url.clone())
-        .send()
-        .await
-        .expect("Failed to download mod.");
-    let total_size = result
-        .content_length()
-        .ok_or(format!("Failed to get content length from {}", url))
-        .unwrap();
-    let mod_path = format!("{}/{}", mods_path, name);
-
-    if !PathBuf::from_str(mod_path.as_str()).unwrap().exists() {
-        match fs::create_dir(mod_path.clone()) {
-            Ok(_) => info!("Successfully created mod folder for {:?}.", name),
-            Err(e) => error!("Failed to create mod folder for {:?}: {}", name, e),
-        }
-    }
-
-    let extension = url.split(".").last().unwrap();
-    let download_path: String;
-    if extension == "zip" {
-        download_path = format!("{}/temp.zip", mod_path.clone());
-    } else {
-        download_path = format!(
-            "{}/{}",
-            mod_path.clone(),
-            url.clone().split("/").last().unwrap()
-        );
-    }
-
-    {
-        let mut file = File::create(download_path.clone()).unwrap();
-        let mut downloaded: u64 = 0;
-        let mut stream = result.bytes_stream();
-        while let Some(item) = stream.next().await {
-            let chunk = item.unwrap();
-            file.write_all(&chunk).unwrap();
-            let new = min(downloaded + (chunk.len() as u64), total_size);
-            downloaded = new;
-            tx.send((((new as f64) / (total_size as f64)) * 100.0).floor() as u8)
-                .expect("Failed to send download progress.");
-        }
-    }
-
-    /*let file_hash = digest_file(download_path.clone()).unwrap();
-    if file_hash.to_lowercase() != mod_hash.to_lowercase() {
-        error!("Failed to verify SHA256 of downloaded file for mod {:?}, re-downloading...", mod_name);
-        install_mod(mod_name.clone(), mod_version, mod_hash, mod_link.clone()).await;
-    } else {
-        info!("Downloaded hash of {:?} matches with that on modlinks.", mod_name);
-    }*/
+/// * `sha256` - The expected SHA256 of the downloaded file
+/// * `options` - How to check and retry the download
+async fn download_mod(
+    tx: &mpsc::Sender<DownloadEvent>,
+    name: String,
+    url: String,
+    sha256: String,
+    options: DownloadOptions,
+) -> Result<PathBuf, DownloadError> {
+    // Downloads are named after the mod; What they contain is only looked at when extracting
+    let download_path = download_path(name.as_str(), url.as_str(), sha256.as_str())?;
 
-    if extension == "zip" {
-        let file = File::open(download_path.clone()).unwrap();
-        let unzipper = Unzipper::new(file, mod_path);
-        match unzipper.unzip() {
-            Ok(_) => info!("Successfully unzipped contents of {}", download_path),
-            Err(e) => error!("Failed to unzip contents of {}: {}", download_path, e),
-        }
+    download_cached(
+        tx,
+        name.as_str(),
+        url.as_str(),
+        sha256.as_str(),
+        download_path.as_path(),
+        options,
+    )
+    .await?;
 
-        fs::remove_file(download_path).unwrap();
-    }
+    Ok(download_path)
 }
 
-/// Move a mod folder out of the Disabled folder if it is there
+/// Download several mods at once on the thread pool, reporting the progress of each; Returns the
+/// paths to the downloaded files by mod name, or the first error once every download has ended
 /// # Arguments
-/// * `mod_name` - The name of the mod folder to move out of the Disabled folder
+/// * `manifests` - The mods to download
 /// * `state` - The state of the application
-fn enable_mod(mod_name: String, state: &AppState) {
-    info!("Enabling mod {:?}", mod_name);
-    let mut app_state = state.0.lock().unwrap();
-    let mods_path = &app_state.settings.mods_path;
-    let mod_path: PathBuf = [mods_path.to_string(), mod_name.clone()].iter().collect();
-    let disabled_mod_path: PathBuf = [
-        mods_path.to_string(),
-        "Disabled".to_string(),
-        mod_name.clone(),
-    ]
-    .iter()
-    .collect();
-    if disabled_mod_path.exists() {
-        match fs::rename(disabled_mod_path.as_path(), mod_path.as_path()) {
-            Ok(_) => info!(
-                "Successfully moved mod {} out of Disabled folder.",
-                mod_name
-            ),
-            Err(e) => error!(
-                "Failed to move mod directory {:?} from Disabled: {}",
-                mod_path.to_str().unwrap(),
-                e
-            ),
+fn download_mods(
+    manifests: &[LocalModManifest],
+    state: &AppState,
+) -> Result<HashMap<String, PathBuf>, DownloadError> {
+    let mut downloads = HashMap::new();
+    if manifests.is_empty() {
+        return Ok(downloads);
+    }
+
+    let runtime = Runtime::new().unwrap();
+    let (tx, rx) = mpsc::channel();
+    {
+        let app_state = state.0.lock().unwrap();
+        for (i, manifest) in manifests.iter().enumerate() {
+            let tx = tx.clone();
+            let handle = runtime.handle().clone();
+            let name = manifest.name.clone();
+            let url = manifest.link.link.clone();
+            let sha256 = manifest.link.sha256.clone();
+            let options = app_state.download_options;
+            // Each job holds a pool thread until its download ends, so the pool size limits how
+            // many mods are downloaded at once
+            app_state.pool.execute(move || {
+                let (event_tx, event_rx) = mpsc::channel();
+                handle.spawn(async move {
+                    let result = download_mod(&event_tx, name, url, sha256, options).await;
+                    let _ = event_tx.send(DownloadEvent::Finished(result));
+                });
+                for event in event_rx {
+                    let finished = matches!(event, DownloadEvent::Finished(_));
+                    let _ = tx.send((i, event));
+                    if finished {
+                        return;
+                    }
+                }
+                // The download ended without reporting back, e.g. because it panicked
+                let _ = tx.send((
+                    i,
+                    DownloadEvent::Finished(Err(DownloadError::Network(
+                        "the download stopped unexpectedly".to_string(),
+                    ))),
+                ));
+            });
         }
-    } else {
-        warn!("Path {:?} does not exist.", mod_path.to_str().unwrap());
     }
+    drop(tx);
 
-    (*app_state)
-        .settings
-        .mod_links
-        .manifests
-        .iter_mut()
-        .for_each(|m| {
-            if m.name == mod_name {
-                m.enabled = true;
+    // The progress line is redrawn in place, which only works on a terminal
+    let is_terminal = io::stdout().is_terminal();
+    let mut progress: Vec<Option<String>> = vec![None; manifests.len()];
+    let mut finished = 0;
+    let mut first_error = None;
+    for (i, event) in rx.iter() {
+        match event {
+            DownloadEvent::Progress(percent) => progress[i] = Some(format!("{}%", percent)),
+            DownloadEvent::Received(bytes) => progress[i] = Some(format_size(bytes)),
+            DownloadEvent::Finished(result) => {
+                finished += 1;
+                match result {
+                    Ok(path) => {
+                        if !is_terminal {
+                            println!(
+                                "Downloaded mod {:?} ({}/{}).",
+                                manifests[i].name,
+                                finished,
+                                manifests.len()
+                            );
+                        }
+                        downloads.insert(manifests[i].name.clone(), path);
+                    }
+                    Err(e) => {
+                        error!("Failed to download mod {:?}: {}", manifests[i].name, e);
+                        first_error.get_or_insert(e);
+                    }
+                }
+                progress[i] = None;
             }
-        });
-}
+        }
 
-/// Manually select the path of the game's executable
-/// # Arguments
-/// * `app` - The mutex guarding the application state
-fn enter_game_path(app: MutexGuard<App>) {
-    warn!("Entering game path manually.");
-    print!("Enter your game path: ");
-    io::stdout().flush().unwrap();
-    let mut entered_path = String::new();
-    io::stdin()
-        .read_line(&mut entered_path)
-        .expect("Error: entered path is not valid.");
-    set_game_path(app, entered_path);
-}
-
-fn set_game_path(mut app: MutexGuard<App>, mut game_path: String) {
-    let mut path = PathBuf::from_str(game_path.trim()).unwrap();
-    let mut mods_path = "".to_string();
-    match SUFFIXES.into_iter().find(|suffix| {
-        let path_buf: PathBuf = [path.clone(), PathBuf::from_str(suffix).unwrap()]
-            .iter()
-            .collect();
-        info!(
-            "Checking selected path: {}",
-            path_buf.clone().to_str().unwrap()
-        );
-        path_buf.exists()
-    }) {
-        Some(suffix) => mods_path = format!("{}/{}/Mods", path.to_str().unwrap(), suffix),
-        None => error!("No managed path found."),
-    }
-    while mods_path.as_str() == "" {
-        println!("Path {} is not a valid game path.", path.to_str().unwrap());
-        print!("Enter your game path: ");
-        io::stdout().flush().unwrap();
-        game_path = String::new();
-        io::stdin()
-            .read_line(&mut game_path)
-            .expect("Error: entered path is not valid.");
-        path = PathBuf::from_str(game_path.trim()).unwrap();
-        match SUFFIXES.into_iter().find(|suffix| {
-            let path_buf: PathBuf = [path.clone(), PathBuf::from_str(suffix).unwrap()]
+        if is_terminal {
+            let active: Vec<String> = progress
                 .iter()
+                .enumerate()
+                .filter_map(|(j, update)| {
+                    update
+                        .as_ref()
+                        .map(|update| format!("{} {}", manifests[j].name, update))
+                })
                 .collect();
-            info!(
-                "Checking selected path: {}",
-                path_buf.clone().to_str().unwrap()
+            // Clear the rest of the previous line, which may have been longer
+            print!(
+                "\x1b[2KDownloading mods ({}/{}): {}\r",
+                finished,
+                manifests.len(),
+                active.join(", ")
             );
-            path_buf.exists()
-        }) {
-            Some(suffix) => mods_path = format!("{}/{}/Mods", path.to_str().unwrap(), suffix),
-            None => error!("No managed path found."),
+            io::stdout().flush().unwrap();
+        }
+        if finished == manifests.len() {
+            break;
         }
     }
-    (*app).settings.mods_path = mods_path;
-    print_and_log(format!("Mods path is now: {}", app.settings.mods_path));
-}
+    if is_terminal {
+        println!();
+    }
+    prune_cache(state.0.lock().unwrap().settings.cache_size_limit);
 
-/// Gracefully exit application
-fn exit_app(state: &AppState) {
-    let state = state.0.lock().unwrap();
-    let settings = state.settings.clone();
-    let base_dir = BaseDirs::new().unwrap();
-    let settings_dir: PathBuf = [base_dir.data_dir().to_str().unwrap(), SETTINGS_FOLDER]
-        .iter()
-        .collect();
-    if !settings_dir.exists() {
-        match fs::create_dir(settings_dir.as_path()) {
-            Ok(_) => info!("Succesfully created settings folder."),
-            Err(e) => error!("Failed to create settings folder: {}", e),
+    match first_error {
+        Some(e) => {
+            downloads.values().for_each(|path| remove_download(path));
+            Err(e)
         }
+        None => Ok(downloads),
     }
-    let settings_path: PathBuf = [settings_dir.to_str().unwrap(), "Settings.json"]
-        .iter()
-        .collect();
-    // Save or create a settings file
-    if settings_path.exists() {
-        let settings_file = File::options()
-            .write(true)
-            .truncate(true)
-            .open(settings_path.as_path())
-            .unwrap();
-        match serde_json::to_writer_pretty(settings_file, &settings) {
-            Ok(_) => info!("Successfully saved settings."),
-            Err(e) => error!("Failed to save settings: {}", e),
-        }
+}
+
+/// The path to download a file to, creating its folder; Downloads are kept apart by the SHA256
+/// they should have, or by their URL if it is not known, so that a partial download is only
+/// ever resumed with the rest of the same file
+/// # Arguments
+/// * `file_name` - The name to give the downloaded file
+/// * `url` - The URL of the file
+/// * `sha256` - The expected SHA256 of the file, or an empty string
+fn download_path(file_name: &str, url: &str, sha256: &str) -> Result<PathBuf, DownloadError> {
+    let key = if sha256.is_empty() {
+        sha256::digest(url)
     } else {
-        let mut settings_file = File::create(settings_path.as_path()).unwrap();
-        let settings_string = serde_json::to_string_pretty(&state.settings).unwrap();
-        match settings_file.write_all(settings_string.as_bytes()) {
-            Ok(_) => info!("Successfully created new settings file."),
-            Err(e) => error!("Failed to create new settings file: {}", e),
-        }
-    }
+        sha256.to_lowercase()
+    };
+    let folder = settings_dir().join("Downloads").join(key);
+    fs::create_dir_all(folder.as_path())
+        .map_err(|e| DownloadError::Io(format!("failed to create downloads folder: {}", e)))?;
+    Ok(folder.join(file_name))
 }
 
-/// Close Hollow Knight before starting the installer
-fn exit_game() {
-    let system = System::new_all();
-    for process in system.processes_by_name("hollow_knight") {
-        match process.kill() {
-            true => info!("Successfully killed hollow_knight process."),
-            false => error!("Failed to kill hollow_knight process."),
-        }
-    }
+/// The path to the file that holds the ETag or Last-Modified date of a partial download
+/// # Arguments
+/// * `path` - The path to the downloaded file
+fn validator_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".validator");
+    path.with_file_name(file_name)
+}
 
-    for process in system.processes_by_name("Hollow Knight") {
-        match process.kill() {
-            true => info!("Successfully killed Hollow Knight process."),
-            false => error!("Failed to kill Hollow Knight process."),
-        }
+/// Delete a downloaded file along with what was kept to resume it, and its folder if nothing
+/// else is left in it
+/// # Arguments
+/// * `path` - The path to the downloaded file
+fn remove_download(path: &Path) {
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(validator_path(path));
+    if let Some(folder) = path.parent() {
+        let _ = fs::remove_dir(folder);
     }
 }
 
-/// Load and return the list of mods from https://raw.githubusercontent.com/hk-modding/modlinks/main/ModLinks.xml
+/// Extract or copy a downloaded mod into a staging folder, check that it produced files and
+/// then move it into the mods folder in one rename, so that a failure never leaves a partial
+/// mod folder behind; The download is deleted either way
 /// # Arguments
-/// * `state` - The state of the application
-fn fetch_mod_list(state: &AppState) {
-    let mut state = state.0.lock().unwrap();
-    let client = reqwest::blocking::Client::new();
-    match client.get(MOD_URL).send() {
-        Ok(response) => {
-            let content = response.text().expect("Failed to get content of mod list.");
-            let mut remote_mod_links = RemoteModLinks::new();
-            let mods_json: String;
-            match quick_xml::de::from_str(content.as_str()) {
-                Ok(value) => {
-                    info!("Successfully parsed ModLinks XML");
-                    remote_mod_links = value;
-                }
-                Err(e) => error!("Failed to parse ModLinks XML: {}", e),
-            }
+/// * `name` - The name of the mod
+/// * `download_path` - The path to the downloaded file
+/// * `dll_name` - The file name to give the download if it is a bare DLL
+/// * `mods_path` - The path to the mods folder
+fn extract_mod(
+    name: &str,
+    download_path: &Path,
+    dll_name: &str,
+    mods_path: &str,
+) -> Result<(), DownloadError> {
+    // The staging folder lives inside the mods folder so the final rename stays on one file system
+    let staging_path: PathBuf = [mods_path, STAGING_FOLDER, name].iter().collect();
+    let mod_path: PathBuf = [mods_path, name].iter().collect();
+    let result = stage_mod(download_path, dll_name, staging_path.as_path()).and_then(|_| {
+        fs::rename(staging_path.as_path(), mod_path.as_path())
+            .map_err(|e| DownloadError::Io(format!("failed to move {:?} into place: {}", name, e)))
+    });
+
+    if staging_path.exists() {
+        let _ = fs::remove_dir_all(staging_path.as_path());
+    }
+    if let Some(staging_root) = staging_path.parent() {
+        // Only succeeds once no other mod is being staged
+        let _ = fs::remove_dir(staging_root);
+    }
+    remove_download(download_path);
+    if result.is_ok() {
+        info!("Successfully installed the contents of {:?}", download_path);
+    }
+    result
+}
+
+/// Extract or copy a downloaded mod into a fresh staging folder
+/// # Arguments
+/// * `download_path` - The path to the downloaded file
+/// * `dll_name` - The file name to give the download if it is a bare DLL
+/// * `staging_path` - The folder to extract the mod into
+fn stage_mod(
+    download_path: &Path,
+    dll_name: &str,
+    staging_path: &Path,
+) -> Result<(), DownloadError> {
+    if staging_path.exists() {
+        fs::remove_dir_all(staging_path)
+            .map_err(|e| DownloadError::Io(format!("failed to clear {:?}: {}", staging_path, e)))?;
+    }
+    fs::create_dir_all(staging_path)
+        .map_err(|e| DownloadError::Io(format!("failed to create {:?}: {}", staging_path, e)))?;
+
+    match artifact_kind(download_path)? {
+        ArtifactKind::Zip => {
+            extract::extract_zip(download_path, staging_path).map_err(DownloadError::Extract)?;
+        }
+        ArtifactKind::Dll => {
+            fs::copy(download_path, staging_path.join(dll_name)).map_err(|e| {
+                DownloadError::Io(format!("failed to copy {:?}: {}", download_path, e))
+            })?;
+        }
+    }
+
+    if list_files(staging_path).is_empty() {
+        return Err(DownloadError::Io(format!(
+            "{:?} did not contain any files",
+            download_path
+        )));
+    }
+
+    Ok(())
+}
+
+/// Tell whether a downloaded file is a zip archive or a bare DLL from its first bytes
+/// # Arguments
+/// * `path` - The path to the downloaded file
+fn artifact_kind(path: &Path) -> Result<ArtifactKind, DownloadError> {
+    let mut magic = [0u8; 4];
+    let read = File::open(path)
+        .and_then(|mut file| file.read(&mut magic))
+        .map_err(|e| DownloadError::Io(format!("failed to read {:?}: {}", path, e)))?;
+    match &magic[..read] {
+        // Local file header, or the end of central directory record of an empty archive
+        [b'P', b'K', 3, 4] | [b'P', b'K', 5, 6] => Ok(ArtifactKind::Zip),
+        [b'M', b'Z', ..] => Ok(ArtifactKind::Dll),
+        _ => Err(DownloadError::Io(format!(
+            "{:?} is neither a zip archive nor a DLL",
+            path
+        ))),
+    }
+}
+
+/// The file name a mod downloaded as a bare DLL is installed under: the last part of its URL if
+/// that names a DLL, else the name of the mod
+/// # Arguments
+/// * `name` - The name of the mod
+/// * `url` - The download link of the mod
+fn dll_file_name(name: &str, url: &str) -> String {
+    let file_name = url
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .rsplit('/')
+        .next()
+        .unwrap_or_default();
+    if file_name.to_lowercase().ends_with(".dll") {
+        file_name.to_string()
+    } else {
+        format!("{}.dll", name)
+    }
+}
+
+/// Stream a file from a URL to disk, reporting the download progress; A partial file left at
+/// the path by an earlier attempt is resumed with a Range request if the server supports it and
+/// the file has not changed on it since, going by its ETag or Last-Modified date
+/// # Arguments
+/// * `tx` - The channel to send the download progress to
+/// * `url` - The URL of the file to download
+/// * `path` - The path to write the downloaded file to
+/// * `timeout` - How long to wait to connect or for more data
+async fn download_file(
+    tx: &mpsc::Sender<DownloadEvent>,
+    url: &str,
+    path: &Path,
+    timeout: Duration,
+) -> Result<(), DownloadError> {
+    if let Some(local_path) = local_path(url) {
+        fs::copy(local_path.as_path(), path)
+            .map_err(|e| DownloadError::Io(format!("failed to copy {:?}: {}", local_path, e)))?;
+        let _ = tx.send(DownloadEvent::Progress(100));
+        return Ok(());
+    }
+
+    let client = reqwest::Client::builder()
+        .connect_timeout(timeout)
+        .build()
+        .map_err(|e| DownloadError::Network(e.to_string()))?;
+    let offset = fs::metadata(path).map_or(0, |metadata| metadata.len());
+    // Without a validator there is no telling whether the partial file is part of what the
+    // server has now, so it is downloaded again from the start
+    let validator = if offset > 0 {
+        fs::read_to_string(validator_path(path)).ok()
+    } else {
+        None
+    };
+    let mut request = client.get(url);
+    if let Some(validator) = &validator {
+        request = request
+            .header(reqwest::header::RANGE, format!("bytes={}-", offset))
+            .header(reqwest::header::IF_RANGE, validator.as_str());
+    }
+    let result = tokio::time::timeout(timeout, request.send())
+        .await
+        .map_err(|_| DownloadError::Network(format!("timed out connecting to {}", url)))?
+        .map_err(|e| DownloadError::Network(e.to_string()))?;
+    if result.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        // The partial file is no smaller than the whole file, so it cannot be resumed
+        remove_download(path);
+        return Err(DownloadError::Network(format!(
+            "could not resume the partial download of {}",
+            url
+        )));
+    }
+    let result = result
+        .error_for_status()
+        .map_err(|e| DownloadError::Network(e.to_string()))?;
+
+    let resumed = validator.is_some() && result.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    if resumed && range_start(result.headers()) != Some(offset) {
+        remove_download(path);
+        return Err(DownloadError::Network(format!(
+            "the server did not resume the partial download of {} where it ended",
+            url
+        )));
+    }
+    let mut downloaded = if resumed {
+        info!("Resuming download of {} from byte {}", url, offset);
+        offset
+    } else {
+        // A strong ETag is preferred, as weak ones cannot be used in If-Range
+        let headers = result.headers();
+        let validator = headers
+            .get(reqwest::header::ETAG)
+            .filter(|etag| !etag.as_bytes().starts_with(b"W/"))
+            .or_else(|| headers.get(reqwest::header::LAST_MODIFIED))
+            .and_then(|value| value.to_str().ok());
+        let saved = match validator {
+            Some(validator) => fs::write(validator_path(path), validator),
+            None => fs::remove_file(validator_path(path)),
+        };
+        if let Err(e) = saved {
+            if e.kind() != io::ErrorKind::NotFound {
+                warn!("Failed to save the validator of {:?}: {}", path, e);
+            }
+        }
+        0
+    };
+    // Chunked responses have no length, so only the bytes received so far can be reported
+    let total_size = result.content_length().map(|length| length + downloaded);
+
+    let mut file = if resumed {
+        OpenOptions::new().append(true).open(path)
+    } else {
+        File::create(path)
+    }
+    .map_err(|e| DownloadError::Io(format!("failed to open {:?}: {}", path, e)))?;
+    let mut stream = result.bytes_stream();
+    loop {
+        let item = tokio::time::timeout(timeout, stream.next())
+            .await
+            .map_err(|_| {
+                DownloadError::Network(format!("timed out waiting for data from {}", url))
+            })?;
+        let chunk = match item {
+            Some(item) => item.map_err(|e| DownloadError::Network(e.to_string()))?,
+            None => break,
+        };
+        file.write_all(&chunk)
+            .map_err(|e| DownloadError::Io(format!("failed to write {:?}: {}", path, e)))?;
+        downloaded += chunk.len() as u64;
+        let _ = tx.send(match total_size {
+            Some(total_size) => DownloadEvent::Progress(
+                (((min(downloaded, total_size) as f64) / (total_size as f64)) * 100.0).floor()
+                    as u8,
+            ),
+            None => DownloadEvent::Received(downloaded),
+        });
+    }
+    if let Some(total_size) = total_size {
+        if downloaded != total_size {
+            return Err(DownloadError::Network(format!(
+                "received {} of {} bytes from {}",
+                downloaded, total_size, url
+            )));
+        }
+    }
+    // The file is complete, so it is never resumed
+    let _ = fs::remove_file(validator_path(path));
+
+    Ok(())
+}
+
+/// The first byte of the file that a partial response starts at, from its Content-Range header
+/// # Arguments
+/// * `headers` - The headers of the response
+fn range_start(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    headers
+        .get(reqwest::header::CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .strip_prefix("bytes ")?
+        .split('-')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Download a file, retrying with exponential backoff when an attempt fails because of the
+/// network; Partial files are kept between attempts so that they can be resumed
+/// # Arguments
+/// * `tx` - The channel to send the download progress to
+/// * `name` - The name of what is being downloaded, used in messages
+/// * `url` - The URL of the file to download
+/// * `path` - The path to write the downloaded file to
+/// * `options` - How to retry the download
+async fn download_with_retries(
+    tx: &mpsc::Sender<DownloadEvent>,
+    name: &str,
+    url: &str,
+    path: &Path,
+    options: DownloadOptions,
+) -> Result<(), DownloadError> {
+    let attempts = options.retries.saturating_add(1);
+    let mut delay = Duration::from_secs(1);
+    for attempt in 1..=attempts {
+        match download_file(tx, url, path, options.timeout).await {
+            Ok(()) => return Ok(()),
+            Err(DownloadError::Network(e)) if attempt < attempts => {
+                eprintln!(
+                    "Download of {:?} failed (attempt {} of {}): {}; retrying in {}s...",
+                    name,
+                    attempt,
+                    attempts,
+                    e,
+                    delay.as_secs()
+                );
+                warn!(
+                    "Download of {:?} failed (attempt {} of {}): {}",
+                    name, attempt, attempts, e
+                );
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+            }
+            Err(DownloadError::Network(e)) => {
+                return Err(DownloadError::Network(format!(
+                    "download of {:?} failed after {} attempts: {}",
+                    name, attempts, e
+                )))
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Copy a file from the download cache if an archive with the expected SHA256 is there, else
+/// download and verify it and add it to the cache; Unverified downloads are never cached
+/// # Arguments
+/// * `tx` - The channel to send the download progress to
+/// * `name` - The name of what is being downloaded, used in messages
+/// * `url` - The URL of the file to download
+/// * `sha256` - The expected SHA256 of the downloaded file
+/// * `path` - The path to write the downloaded file to
+/// * `options` - How to check and retry the download
+async fn download_cached(
+    tx: &mpsc::Sender<DownloadEvent>,
+    name: &str,
+    url: &str,
+    sha256: &str,
+    path: &Path,
+    options: DownloadOptions,
+) -> Result<(), DownloadError> {
+    let cache_path = if options.skip_verify || sha256.is_empty() {
+        None
+    } else {
+        Some(
+            settings_dir()
+                .join(CACHE_FOLDER)
+                .join(sha256.to_lowercase()),
+        )
+    };
+
+    if let Some(cached) = cache_path
+        .as_deref()
+        .and_then(|cache_path| cached_file(cache_path, sha256))
+    {
+        fs::copy(cached.as_path(), path)
+            .map_err(|e| DownloadError::Io(format!("failed to copy {:?}: {}", cached, e)))?;
+        info!("Using cached download of {:?}.", name);
+        let _ = tx.send(DownloadEvent::Progress(100));
+        return Ok(());
+    }
+
+    download_verified(tx, name, url, sha256, path, options).await?;
+
+    if let Some(cache_path) = cache_path {
+        let cached = cache_path.join(path.file_name().unwrap());
+        // Failing to cache a download does not fail the download
+        if let Err(e) =
+            fs::create_dir_all(cache_path.as_path()).and_then(|_| fs::copy(path, cached.as_path()))
+        {
+            warn!("Failed to cache download of {:?}: {}", name, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Find the cached archive in a cache entry and check that it still matches its SHA256; Entries
+/// that do not match are removed
+/// # Arguments
+/// * `cache_path` - The folder of the cache entry
+/// * `sha256` - The SHA256 the cached archive should have
+fn cached_file(cache_path: &Path, sha256: &str) -> Option<PathBuf> {
+    let cached = fs::read_dir(cache_path)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| path.is_file())?;
+    match digest_file(cached.as_path()) {
+        Ok(hash) if hash.eq_ignore_ascii_case(sha256) => Some(cached),
+        _ => {
+            warn!("Cached archive {:?} is corrupt, removing it.", cached);
+            let _ = fs::remove_dir_all(cache_path);
+            None
+        }
+    }
+}
+
+/// Whether the download cache holds an intact archive with a SHA256
+/// # Arguments
+/// * `sha256` - The SHA256 of the archive
+fn is_cached(sha256: &str) -> bool {
+    !sha256.is_empty()
+        && cached_file(
+            settings_dir()
+                .join(CACHE_FOLDER)
+                .join(sha256.to_lowercase())
+                .as_path(),
+            sha256,
+        )
+        .is_some()
+}
+
+/// The entries of the download cache as (SHA256, archive, size in bytes, time cached), oldest
+/// first
+fn cache_entries() -> Vec<(String, PathBuf, u64, SystemTime)> {
+    let cache_dir = settings_dir().join(CACHE_FOLDER);
+    let mut entries: Vec<(String, PathBuf, u64, SystemTime)> = match fs::read_dir(cache_dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let sha256 = entry.file_name().to_string_lossy().to_string();
+                let archive = fs::read_dir(entry.path())
+                    .ok()?
+                    .filter_map(|file| file.ok())
+                    .find(|file| file.path().is_file())?;
+                let metadata = archive.metadata().ok()?;
+                Some((
+                    sha256,
+                    archive.path(),
+                    metadata.len(),
+                    metadata.modified().unwrap_or(UNIX_EPOCH),
+                ))
+            })
+            .collect(),
+        Err(_) => vec![],
+    };
+    entries.sort_by_key(|(_, _, _, cached_at)| *cached_at);
+    entries
+}
+
+/// Delete the oldest cached archives until the cache is no bigger than a limit
+/// # Arguments
+/// * `limit` - The size limit in MiB
+fn prune_cache(limit: u64) -> Vec<(String, PathBuf, u64, SystemTime)> {
+    let limit = limit * 1024 * 1024;
+    let entries = cache_entries();
+    let mut total: u64 = entries.iter().map(|(_, _, size, _)| size).sum();
+    let mut removed = vec![];
+    for entry in entries {
+        if total <= limit {
+            break;
+        }
+        let cache_path = settings_dir().join(CACHE_FOLDER).join(entry.0.as_str());
+        match fs::remove_dir_all(cache_path.as_path()) {
+            Ok(_) => {
+                info!("Pruned cached archive {:?}.", entry.1);
+                total -= entry.2;
+                removed.push(entry);
+            }
+            Err(e) => error!("Failed to prune cached archive {:?}: {}", entry.1, e),
+        }
+    }
+    removed
+}
+
+/// Handle the `install-dirs` subcommands
+/// # Arguments
+/// * `cmd` - The install-dirs subcommand to run
+/// * `state` - The state of the application
+fn manage_install_dirs(cmd: InstallDirCommand, state: &AppState) -> Result<(), String> {
+    let mut app_state = state.0.lock().unwrap();
+    // Listing shows the state of the installation in use as it is now
+    store_install_dir(&mut app_state);
+    match cmd {
+        InstallDirCommand::List => {
+            let settings = &app_state.settings;
+            if settings.install_dirs.is_empty() {
+                println!("No installations have been added.");
+                return Ok(());
+            }
+            for install_dir in settings.install_dirs.iter() {
+                println!(
+                    "{}{}{}",
+                    install_dir.name,
+                    if install_dir.name == settings.default_install_dir {
+                        " (default)"
+                    } else {
+                        ""
+                    },
+                    if Some(&install_dir.name) == app_state.install_dir.as_ref() {
+                        " (selected)"
+                    } else {
+                        ""
+                    }
+                );
+                println!("\tMods path: {}", install_dir.mods_path);
+                println!("\tInstalled mods: {}", install_dir.installed_mods.len());
+                println!(
+                    "\tModding API: {}",
+                    if install_dir.api_version.is_empty() {
+                        "not installed"
+                    } else {
+                        install_dir.api_version.as_str()
+                    }
+                );
+            }
+        }
+        InstallDirCommand::Add {
+            name,
+            path,
+            default,
+        } => {
+            if app_state
+                .settings
+                .install_dirs
+                .iter()
+                .any(|install_dir| install_dir.name == name)
+            {
+                return Err(format!("there is already an installation named {:?}", name));
+            }
+            let managed_path = detect::managed_path(Path::new(path.trim()))
+                .ok_or_else(|| format!("{:?} does not contain a Managed folder", path))?;
+            let mods_path = managed_path.join("Mods");
+            if !mods_path.exists() {
+                fs::create_dir(mods_path.as_path())
+                    .map_err(|e| format!("failed to create {:?}: {}", mods_path, e))?;
+            }
+            app_state.settings.install_dirs.push(InstallDir {
+                name: name.clone(),
+                mods_path: mods_path.to_str().unwrap().to_string(),
+                installed_mods: vec![],
+                api_version: String::new(),
+                api_sha256: String::new(),
+                api_url: String::new(),
+                api_files: vec![],
+            });
+            print_and_log(format!(
+                "Added installation {:?} with mods path {}.",
+                name,
+                mods_path.display()
+            ));
+            if default || app_state.settings.default_install_dir.is_empty() {
+                app_state.settings.default_install_dir = name.clone();
+                print_and_log(format!("Installation {:?} is now the default.", name));
+            }
+        }
+        InstallDirCommand::Remove { name } => {
+            let count = app_state.settings.install_dirs.len();
+            app_state
+                .settings
+                .install_dirs
+                .retain(|install_dir| install_dir.name != name);
+            if app_state.settings.install_dirs.len() == count {
+                return Err(format!("there is no installation named {:?}", name));
+            }
+            if app_state.install_dir.as_ref() == Some(&name) {
+                // Keep it from being saved again on exit or moved into a new installation
+                app_state.install_dir = None;
+                app_state.settings.mods_path = String::new();
+                app_state.settings.installed_mods = vec![];
+                app_state.settings.api_version = String::new();
+                app_state.settings.api_sha256 = String::new();
+                app_state.settings.api_url = String::new();
+                app_state.settings.api_files = vec![];
+            }
+            if app_state.settings.default_install_dir == name {
+                app_state.settings.default_install_dir = app_state
+                    .settings
+                    .install_dirs
+                    .first()
+                    .map(|install_dir| install_dir.name.clone())
+                    .unwrap_or_default();
+            }
+            print_and_log(format!("Removed installation {:?}.", name));
+        }
+        InstallDirCommand::Default { name } => {
+            if !app_state
+                .settings
+                .install_dirs
+                .iter()
+                .any(|install_dir| install_dir.name == name)
+            {
+                return Err(format!("there is no installation named {:?}", name));
+            }
+            app_state.settings.default_install_dir = name.clone();
+            print_and_log(format!("Installation {:?} is now the default.", name));
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle the `cache` subcommands
+/// # Arguments
+/// * `cmd` - The cache subcommand to run
+/// * `state` - The state of the application
+fn manage_cache(cmd: CacheCommand, state: &AppState) {
+    let limit = state.0.lock().unwrap().settings.cache_size_limit;
+    match cmd {
+        CacheCommand::List => {
+            let entries = cache_entries();
+            if entries.is_empty() {
+                println!("The download cache is empty.");
+                return;
+            }
+            let app_state = state.0.lock().unwrap();
+            println!("{:<14}{:<12}{:<22}Mod", "SHA256", "Size", "Cached");
+            for (sha256, archive, size, cached_at) in entries {
+                let owner = app_state
+                    .settings
+                    .mod_links
+                    .manifests
+                    .iter()
+                    .find(|manifest| manifest.link.sha256.eq_ignore_ascii_case(sha256.as_str()))
+                    .map(|manifest| format!("{} {}", manifest.name, manifest.version))
+                    .unwrap_or_else(|| archive.file_name().unwrap().to_string_lossy().to_string());
+                let cached_at = cached_at
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |duration| duration.as_secs());
+                println!(
+                    "{:<14}{:<12}{:<22}{}",
+                    &sha256[..sha256.len().min(12)],
+                    format_size(size),
+                    format_timestamp(cached_at),
+                    owner
+                );
+            }
+        }
+        CacheCommand::Size => {
+            let entries = cache_entries();
+            let total: u64 = entries.iter().map(|(_, _, size, _)| size).sum();
+            println!(
+                "{} cached archives, {} (limit {})",
+                entries.len(),
+                format_size(total),
+                format_size(limit * 1024 * 1024)
+            );
+        }
+        CacheCommand::Clean => {
+            let entries = cache_entries();
+            let total: u64 = entries.iter().map(|(_, _, size, _)| size).sum();
+            let cache_dir = settings_dir().join(CACHE_FOLDER);
+            if cache_dir.exists() {
+                if let Err(e) = fs::remove_dir_all(cache_dir.as_path()) {
+                    error!("Failed to delete the download cache: {}", e);
+                    eprintln!("Error: failed to delete the download cache: {}", e);
+                    return;
+                }
+            }
+            print_and_log(format!(
+                "Deleted {} cached archives ({}).",
+                entries.len(),
+                format_size(total)
+            ));
+        }
+        CacheCommand::Prune { max_size } => {
+            let removed = prune_cache(max_size.unwrap_or(limit));
+            let total: u64 = removed.iter().map(|(_, _, size, _)| size).sum();
+            print_and_log(format!(
+                "Pruned {} cached archives ({}).",
+                removed.len(),
+                format_size(total)
+            ));
+        }
+    }
+}
+
+/// Download a file and check it against its expected SHA256, re-downloading it a few times
+/// before giving up if the hashes do not match
+/// # Arguments
+/// * `tx` - The channel to send the download progress to
+/// * `name` - The name of what is being downloaded, used in messages
+/// * `url` - The URL of the file to download
+/// * `sha256` - The expected SHA256 of the downloaded file
+/// * `path` - The path to write the downloaded file to
+/// * `options` - How to check and retry the download
+async fn download_verified(
+    tx: &mpsc::Sender<DownloadEvent>,
+    name: &str,
+    url: &str,
+    sha256: &str,
+    path: &Path,
+    options: DownloadOptions,
+) -> Result<(), DownloadError> {
+    let mut attempt = 1;
+    loop {
+        download_with_retries(tx, name, url, path, options).await?;
+        if options.skip_verify {
+            warn!("Skipping SHA256 verification of {:?}.", name);
+            return Ok(());
+        }
+
+        let file_hash = digest_file(path)
+            .map_err(|e| DownloadError::Io(format!("failed to hash {:?}: {}", path, e)))?;
+        if file_hash.eq_ignore_ascii_case(sha256) {
+            info!("Downloaded hash of {:?} matches with its manifest.", name);
+            return Ok(());
+        }
+
+        remove_download(path);
+        if attempt >= VERIFY_ATTEMPTS {
+            return Err(DownloadError::HashMismatch {
+                name: name.to_string(),
+                expected: sha256.to_lowercase(),
+                actual: file_hash.to_lowercase(),
+            });
+        }
+        warn!(
+            "Failed to verify SHA256 of downloaded file for {:?} (attempt {} of {}), re-downloading...",
+            name, attempt, VERIFY_ATTEMPTS
+        );
+        attempt += 1;
+    }
+}
+
+/// Move a mod folder out of the Disabled folder if it is there
+/// # Arguments
+/// * `mod_name` - The name of the mod folder to move out of the Disabled folder
+/// * `state` - The state of the application
+fn enable_mod(mod_name: String, state: &AppState) {
+    info!("Enabling mod {:?}", mod_name);
+    let mut app_state = state.0.lock().unwrap();
+    let mods_path = &app_state.settings.mods_path;
+    let mod_path: PathBuf = [mods_path.to_string(), mod_name.clone()].iter().collect();
+    let disabled_mod_path: PathBuf = [
+        mods_path.to_string(),
+        "Disabled".to_string(),
+        mod_name.clone(),
+    ]
+    .iter()
+    .collect();
+    if disabled_mod_path.exists() {
+        match fs::rename(disabled_mod_path.as_path(), mod_path.as_path()) {
+            Ok(_) => info!(
+                "Successfully moved mod {} out of Disabled folder.",
+                mod_name
+            ),
+            Err(e) => error!(
+                "Failed to move mod directory {:?} from Disabled: {}",
+                mod_path.to_str().unwrap(),
+                e
+            ),
+        }
+    } else {
+        warn!("Path {:?} does not exist.", mod_path.to_str().unwrap());
+    }
+
+    app_state
+        .settings
+        .mod_links
+        .manifests
+        .iter_mut()
+        .for_each(|m| {
+            if m.name == mod_name {
+                m.enabled = true;
+            }
+        });
+}
+
+/// Manually select the path of the game's executable, asking again until the user enters a
+/// valid one
+/// # Arguments
+/// * `app` - The application
+fn enter_game_path(app: &mut App) -> Result<(), String> {
+    warn!("Entering game path manually.");
+    loop {
+        let game_path = prompt(
+            "Enter your game path: ",
+            "no game path was given; pass the folder that contains the Hollow Knight executable \
+             with --game-path",
+            app.non_interactive,
+        )?;
+        match set_game_path(app, game_path.as_str()) {
+            Ok(_) => return Ok(()),
+            Err(e) => println!("{}", e),
+        }
+    }
+}
+
+/// Set the mods path to the Mods folder of a game, in whichever of the `SUFFIXES` layouts it
+/// uses, and create the folder if needed
+/// # Arguments
+/// * `app` - The application
+/// * `game_path` - The folder that contains the game executable
+fn set_game_path(app: &mut App, game_path: &str) -> Result<(), String> {
+    let path = PathBuf::from(game_path.trim());
+    info!("Checking selected path: {}", path.display());
+    let managed_path = detect::managed_path(path.as_path()).ok_or_else(|| {
+        format!(
+            "path {} is not a valid game path; it has no Managed folder",
+            path.display()
+        )
+    })?;
+    let mods_path = managed_path.join("Mods");
+    if !mods_path.exists() {
+        match fs::create_dir(mods_path.as_path()) {
+            Ok(_) => info!("Successfully created mods directory."),
+            Err(e) => error!("Error creating mods folder: {}", e),
+        }
+    }
+    app.settings.mods_path = mods_path.to_str().unwrap().to_string();
+    print_and_log(format!("Mods path is now: {}", app.settings.mods_path));
+    Ok(())
+}
+
+/// Make a named installation the one that commands act on by loading its mods path, installed
+/// mods and Modding API state into settings; Settings from before named installations are moved
+/// into one first
+/// # Arguments
+/// * `name` - The name of the installation, or None for the default one
+/// * `state` - The state of the application
+fn select_install_dir(name: Option<String>, state: &AppState) -> Result<(), String> {
+    let mut app_state = state.0.lock().unwrap();
+    let settings = &mut app_state.settings;
+    if settings.install_dirs.is_empty() && !settings.mods_path.is_empty() {
+        settings.install_dirs.push(InstallDir {
+            name: DEFAULT_INSTALL_DIR.to_string(),
+            mods_path: settings.mods_path.clone(),
+            installed_mods: settings.installed_mods.clone(),
+            api_version: settings.api_version.clone(),
+            api_sha256: settings.api_sha256.clone(),
+            api_url: settings.api_url.clone(),
+            api_files: settings.api_files.clone(),
+        });
+        settings.default_install_dir = DEFAULT_INSTALL_DIR.to_string();
+    }
+
+    let install_dir = match name {
+        Some(name) => settings
+            .install_dirs
+            .iter()
+            .find(|install_dir| install_dir.name == name)
+            .cloned()
+            .ok_or_else(|| format!("there is no installation named {:?}", name))?,
+        None => match settings
+            .install_dirs
+            .iter()
+            .find(|install_dir| install_dir.name == settings.default_install_dir)
+            .or_else(|| settings.install_dirs.first())
+        {
+            Some(install_dir) => install_dir.clone(),
+            // The first installation is made once the game is found
+            None => {
+                app_state.install_dir = Some(DEFAULT_INSTALL_DIR.to_string());
+                return Ok(());
+            }
+        },
+    };
+
+    info!("Using installation {:?}", install_dir.name);
+    settings.mods_path = install_dir.mods_path;
+    settings.installed_mods = install_dir.installed_mods;
+    settings.api_version = install_dir.api_version;
+    settings.api_sha256 = install_dir.api_sha256;
+    settings.api_url = install_dir.api_url;
+    settings.api_files = install_dir.api_files;
+    let mods_path = settings.mods_path.clone();
+    mark_installed_mods(&mut settings.mod_links.manifests, mods_path.as_str());
+    app_state.install_dir = Some(install_dir.name);
+
+    Ok(())
+}
+
+/// Save the mods path, installed mods and Modding API state in settings back into the
+/// installation they were loaded from
+/// # Arguments
+/// * `app` - The application
+fn store_install_dir(app: &mut App) {
+    let name = match &app.install_dir {
+        Some(name) => name.clone(),
+        None => return,
+    };
+    let settings = &mut app.settings;
+    if settings.mods_path.is_empty() {
+        return;
+    }
+    let install_dir = InstallDir {
+        name: name.clone(),
+        mods_path: settings.mods_path.clone(),
+        installed_mods: settings.installed_mods.clone(),
+        api_version: settings.api_version.clone(),
+        api_sha256: settings.api_sha256.clone(),
+        api_url: settings.api_url.clone(),
+        api_files: settings.api_files.clone(),
+    };
+    match settings
+        .install_dirs
+        .iter_mut()
+        .find(|install_dir| install_dir.name == name)
+    {
+        Some(existing) => *existing = install_dir,
+        None => settings.install_dirs.push(install_dir),
+    }
+    if settings.default_install_dir.is_empty() {
+        settings.default_install_dir = name;
+    }
+}
+
+/// Gracefully exit application
+fn exit_app(state: &AppState) {
+    let mut state = state.0.lock().unwrap();
+    store_install_dir(&mut state);
+    let settings = state.settings.clone();
+    let base_dir = BaseDirs::new().unwrap();
+    let settings_dir: PathBuf = [base_dir.data_dir().to_str().unwrap(), SETTINGS_FOLDER]
+        .iter()
+        .collect();
+    if !settings_dir.exists() {
+        match fs::create_dir(settings_dir.as_path()) {
+            Ok(_) => info!("Succesfully created settings folder."),
+            Err(e) => error!("Failed to create settings folder: {}", e),
+        }
+    }
+    let settings_path: PathBuf = [settings_dir.to_str().unwrap(), "Settings.json"]
+        .iter()
+        .collect();
+    // Save or create a settings file
+    if settings_path.exists() {
+        let settings_file = File::options()
+            .write(true)
+            .truncate(true)
+            .open(settings_path.as_path())
+            .unwrap();
+        match serde_json::to_writer_pretty(settings_file, &settings) {
+            Ok(_) => info!("Successfully saved settings."),
+            Err(e) => error!("Failed to save settings: {}", e),
+        }
+    } else {
+        let mut settings_file = File::create(settings_path.as_path()).unwrap();
+        let settings_string = serde_json::to_string_pretty(&state.settings).unwrap();
+        match settings_file.write_all(settings_string.as_bytes()) {
+            Ok(_) => info!("Successfully created new settings file."),
+            Err(e) => error!("Failed to create new settings file: {}", e),
+        }
+    }
+}
+
+/// Close Hollow Knight before starting the installer
+fn exit_game() {
+    let system = System::new_all();
+    for process in system.processes_by_name("hollow_knight") {
+        match process.kill() {
+            true => info!("Successfully killed hollow_knight process."),
+            false => error!("Failed to kill hollow_knight process."),
+        }
+    }
+
+    for process in system.processes_by_name("Hollow Knight") {
+        match process.kill() {
+            true => info!("Successfully killed Hollow Knight process."),
+            false => error!("Failed to kill Hollow Knight process."),
+        }
+    }
+}
+
+/// Load and return the list of mods from the configured mod sources and ModLinks URLs
+/// # Arguments
+/// * `state` - The state of the application
+fn fetch_mod_list(state: &AppState) {
+    let mut state = state.0.lock().unwrap();
+    let mut sources: Vec<(String, Vec<String>)> = state
+        .settings
+        .mod_sources
+        .iter()
+        .map(|source| {
+            let mut urls = vec![source.url.clone()];
+            urls.extend(source.mirrors.clone());
+            (source.name.clone(), urls)
+        })
+        .collect();
+    sources.push((OFFICIAL_SOURCE.to_string(), state.mod_links_urls.clone()));
+    match merge_mod_sources(sources, state.offline) {
+        Some(remote_mod_links) => {
+            let mods_json: String = serde_json::to_string_pretty(&remote_mod_links).unwrap();
+            state.settings.mod_links = serde_json::from_str(mods_json.as_str()).unwrap();
+            let mods_path = state.settings.mods_path.clone();
+            mark_installed_mods(&mut state.settings.mod_links.manifests, mods_path.as_str());
+        }
+        None => error!("Failed to fetch mod links."),
+    }
+}
+
+/// Mark which mods of a mod list are installed and enabled in a mods folder
+/// # Arguments
+/// * `manifests` - The manifests of the mods
+/// * `mods_path` - The path to the mods folder
+fn mark_installed_mods(manifests: &mut [LocalModManifest], mods_path: &str) {
+    let disabled_path: PathBuf = [mods_path, "Disabled"].iter().collect();
+    for manifest in manifests.iter_mut() {
+        let mod_path: PathBuf = [mods_path, manifest.name.as_str()].iter().collect();
+        let disabled_mod_path = disabled_path.join(manifest.name.as_str());
+        manifest.installed = mod_path.exists() || disabled_mod_path.exists();
+        manifest.enabled = mod_path.exists() && !disabled_mod_path.exists();
+    }
+}
+
+/// Fetch the mod links of several sources and merge them into one list; Sources earlier in the
+/// list take precedence when more than one provides a mod of the same name
+/// # Arguments
+/// * `sources` - The names and URLs of each source, in order of precedence
+/// * `offline` - Whether to only use the cached copies of the mod links
+fn merge_mod_sources(sources: Vec<(String, Vec<String>)>, offline: bool) -> Option<RemoteModLinks> {
+    let mut merged: Option<RemoteModLinks> = None;
+    for (source_name, urls) in sources {
+        let file_name = source_cache_name(source_name.as_str(), &urls);
+        let mod_links = match fetch_links::<RemoteModLinks>(&urls, file_name.as_str(), offline) {
+            Some(mod_links) => mod_links,
+            None => {
+                error!("Failed to fetch mod links from source {:?}.", source_name);
+                continue;
+            }
+        };
+
+        let merged = merged.get_or_insert(RemoteModLinks { manifests: vec![] });
+        add_source_mods(merged, source_name.as_str(), mod_links);
+    }
+
+    merged
+}
+
+/// Get the name of the cached copy of a mod source's mod links; The URL is hashed into the name
+/// so that sources whose names only differ in punctuation do not share a cached copy
+/// # Arguments
+/// * `source_name` - The name of the source
+/// * `urls` - The URLs of the source, in order of preference
+fn source_cache_name(source_name: &str, urls: &[String]) -> String {
+    if source_name == OFFICIAL_SOURCE {
+        return "ModLinks.xml".to_string();
+    }
+
+    let safe_name: String = source_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let url = urls.first().map_or("", |url| url.as_str());
+    let hash = sha256::digest(format!("{}\n{}", source_name, url));
+    format!("ModLinks-{}-{}.xml", safe_name, &hash[..16])
+}
 
-            let saved_manifests: Vec<LocalModManifest> = vec![];
+/// Add the mods of a source to the merged mod links, skipping those a source earlier in the
+/// list already provides
+/// # Arguments
+/// * `merged` - The mod links merged so far
+/// * `source_name` - The name of the source
+/// * `mod_links` - The mod links of the source
+fn add_source_mods(merged: &mut RemoteModLinks, source_name: &str, mod_links: RemoteModLinks) {
+    for mut manifest in mod_links.manifests {
+        manifest.source = source_name.to_string();
+        match merged
+            .manifests
+            .iter()
+            .find(|existing| existing.name == manifest.name)
+        {
+            Some(existing) => {
+                eprintln!(
+                    "Warning: mod {:?} is provided by both {:?} and {:?}; using {:?}.",
+                    manifest.name, existing.source, source_name, existing.source
+                );
+                warn!(
+                    "Mod {:?} from source {:?} is shadowed by source {:?}.",
+                    manifest.name, source_name, existing.source
+                );
+            }
+            None => merged.manifests.push(manifest),
+        }
+    }
+}
+
+/// Load and return the Modding API manifest from the configured ApiLinks URLs
+/// # Arguments
+/// * `urls` - The URLs of ApiLinks, in order of preference
+/// * `offline` - Whether to only use the cached copy of the manifest
+fn fetch_api_links(urls: &[String], offline: bool) -> Option<ApiLinks> {
+    fetch_links(urls, "ApiLinks.xml", offline)
+}
+
+/// Fetch and parse a links XML file from the first URL that works, saving the last good copy
+/// to the settings folder so that it can be used when the network is unavailable or offline
+/// mode is enabled
+/// # Arguments
+/// * `urls` - The URLs or file:// paths of the links file, in order of preference
+/// * `file_name` - The name of the cached copy in the settings folder
+/// * `offline` - Whether to skip the network and only use the cached copy
+fn fetch_links<T: DeserializeOwned>(urls: &[String], file_name: &str, offline: bool) -> Option<T> {
+    let cache_path = settings_dir().join(file_name);
+    if !offline {
+        for url in urls {
+            match read_url(url) {
+                Ok(content) => match quick_xml::de::from_str(content.as_str()) {
+                    Ok(value) => {
+                        info!("Successfully parsed {} from {}.", file_name, url);
+                        match fs::write(cache_path.as_path(), content) {
+                            Ok(_) => info!("Saved cached copy of {}.", file_name),
+                            Err(e) => error!("Failed to save cached copy of {}: {}", file_name, e),
+                        }
+                        return Some(value);
+                    }
+                    Err(e) => error!("Failed to parse {} from {}: {}", file_name, url, e),
+                },
+                Err(e) => error!("Failed to fetch {} from {}: {}", file_name, url, e),
+            }
+        }
+    }
+
+    let content = match fs::read_to_string(cache_path.as_path()) {
+        Ok(content) => content,
+        Err(e) => {
+            error!("No cached copy of {} is available: {}", file_name, e);
+            return None;
+        }
+    };
+    let age = fs::metadata(cache_path.as_path())
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .map(|elapsed| format_age(elapsed.as_secs()))
+        .unwrap_or_else(|| "an unknown time".to_string());
+    eprintln!("Using cached {} from {} ago.", file_name, age);
+    info!("Using cached {} from {} ago.", file_name, age);
+    match quick_xml::de::from_str(content.as_str()) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            error!("Failed to parse cached {}: {}", file_name, e);
+            None
+        }
+    }
+}
+
+/// Install the latest release of the Modding API for the game's platform listed in ApiLinks
+/// # Arguments
+/// * `state` - The state of the application
+fn install_api(state: &AppState) -> Result<(), DownloadError> {
+    let (api_links_urls, offline, mods_path) = {
+        let app_state = state.0.lock().unwrap();
+        (
+            app_state.api_links_urls.clone(),
+            app_state.offline,
+            app_state.settings.mods_path.clone(),
+        )
+    };
+    let platform = api_platform(mods_path.as_str())?;
+    let release = latest_api_release(&api_links_urls, offline, platform)?;
+    install_api_release(&release, state)
+}
+
+/// Get the platform of the game in a mods folder, whose build of the Modding API it needs
+/// # Arguments
+/// * `mods_path` - The path to the mods folder
+fn api_platform(mods_path: &str) -> Result<Platform, DownloadError> {
+    let managed_path = Path::new(mods_path)
+        .parent()
+        .unwrap_or(Path::new(mods_path));
+    detect::platform(managed_path).ok_or_else(|| {
+        DownloadError::Network(format!(
+            "the Modding API is not available for {}",
+            std::env::consts::OS
+        ))
+    })
+}
+
+/// Get the latest release of the Modding API for a platform from ApiLinks
+/// # Arguments
+/// * `urls` - The URLs to fetch ApiLinks from, in order of preference
+/// * `offline` - Whether to use the cached ApiLinks instead of fetching it
+/// * `platform` - The platform the game is built for
+fn latest_api_release(
+    urls: &[String],
+    offline: bool,
+    platform: Platform,
+) -> Result<ApiRelease, DownloadError> {
+    latest_api(urls, offline).map(|api| api.release(platform))
+}
+
+/// Get the latest release of the Modding API from ApiLinks with the archive of every platform
+/// # Arguments
+/// * `urls` - The URLs to fetch ApiLinks from, in order of preference
+/// * `offline` - Whether to use the cached ApiLinks instead of fetching it
+fn latest_api(urls: &[String], offline: bool) -> Result<LockedApi, DownloadError> {
+    let api_links = fetch_api_links(urls, offline)
+        .ok_or_else(|| DownloadError::Network("failed to fetch API links".to_string()))?;
+    let links = &api_links.manifest.links;
+    let locked_link = |api_link: &ApiLink| LockedApiLink {
+        url: api_link.link.clone(),
+        sha256: api_link.sha256.clone(),
+    };
+    Ok(LockedApi {
+        version: api_links.manifest.version.clone(),
+        linux: locked_link(&links.linux),
+        mac: locked_link(&links.mac),
+        windows: locked_link(&links.windows),
+        files: api_links.manifest.files.files.clone(),
+    })
+}
+
+/// Download a release of the Modding API and replace local files with its contents if their
+/// hashes do not match; The release is only recorded once every file is in place
+/// # Arguments
+/// * `release` - The release of the Modding API to install
+/// * `state` - The state of the application
+fn install_api_release(release: &ApiRelease, state: &AppState) -> Result<(), DownloadError> {
+    let mut app_state = state.0.lock().unwrap();
+    let mods_path = &app_state.settings.mods_path;
+    // The Modding API can still be installed offline from an archive that is already in the cache
+    if app_state.offline
+        && (app_state.download_options.skip_verify || !is_cached(release.sha256.as_str()))
+    {
+        return Err(DownloadError::Network(format!(
+            "cannot download the Modding API {} in offline mode",
+            release.version
+        )));
+    }
+    // The file names come from ApiLinks or a lockfile, so they get the same checks as the
+    // entries of an archive before anything is written next to them
+    if let Some(file) = release.files.iter().find(|file| {
+        extract::relative_path(file.as_str()).map_or(true, |path| path.as_os_str().is_empty())
+    }) {
+        return Err(DownloadError::Extract(ExtractError::UnsafePath(
+            file.clone(),
+        )));
+    }
+    warn_api_target(
+        release.version.as_str(),
+        release.url.as_str(),
+        mods_path.as_str(),
+    );
 
-            // If save mod links are empty, then this is a first run of the app.
-            if saved_manifests.len() > 0 {
-                for manifest in remote_mod_links.clone().manifests {
-                    if !saved_manifests
-                        .clone()
-                        .into_iter()
-                        .map(|m| serde_json::to_string(&m.name).unwrap())
-                        .collect::<Vec<String>>()
-                        .contains(&manifest.name)
-                    {
-                        // new_mods.push(manifest.name.clone());
-                    }
+    let archive_path = download_path(
+        "ModdingApi.zip",
+        release.url.as_str(),
+        release.sha256.as_str(),
+    )?;
+    let managed_path: PathBuf = [mods_path.as_str(), ".."].iter().collect();
+    let staging_path = settings_dir().join(API_STAGING_FOLDER);
+    // Replaced files are kept inside the mods folder so moving them aside stays on one file system
+    let backup_path: PathBuf = [mods_path.as_str(), BACKUP_FOLDER, "ModdingApi"]
+        .iter()
+        .collect();
 
-                    if saved_manifests
-                        .clone()
-                        .into_iter()
-                        .map(|m| serde_json::to_string(&m.name).unwrap())
-                        .collect::<Vec<String>>()
-                        .contains(&manifest.name)
-                        && !saved_manifests
-                            .clone()
-                            .into_iter()
-                            .map(|m| serde_json::to_string(&m.version).unwrap())
-                            .collect::<Vec<String>>()
-                            .contains(&manifest.version)
-                    {
-                        // outdated_mods.push(manifest.name);
-                    }
-                }
+    let (tx, rx) = mpsc::channel();
+    let runtime = Runtime::new().unwrap();
+    {
+        let url = release.url.clone();
+        let sha256 = release.sha256.clone();
+        let archive_path = archive_path.clone();
+        let options = app_state.download_options;
+        runtime.spawn(async move {
+            let result = download_cached(
+                &tx,
+                "Modding API",
+                url.as_str(),
+                sha256.as_str(),
+                archive_path.as_path(),
+                options,
+            )
+            .await
+            .map(|_| archive_path);
+            let _ = tx.send(DownloadEvent::Finished(result));
+        });
+    }
+    let mut result = Err(DownloadError::Network(
+        "the download stopped unexpectedly".to_string(),
+    ));
+    let is_terminal = io::stdout().is_terminal();
+    if !is_terminal {
+        println!("Downloading the Modding API...");
+    }
+    for event in rx.iter() {
+        match event {
+            DownloadEvent::Progress(_) | DownloadEvent::Received(_) if !is_terminal => continue,
+            DownloadEvent::Progress(percent) => {
+                print!("Downloading the Modding API: {}%\r", percent)
             }
+            DownloadEvent::Received(bytes) => {
+                print!(
+                    "\x1b[2KDownloading the Modding API: {}\r",
+                    format_size(bytes)
+                )
+            }
+            DownloadEvent::Finished(finished) => {
+                result = finished;
+                break;
+            }
+        }
+        io::stdout().flush().unwrap();
+    }
+    if is_terminal {
+        println!();
+    }
+    result?;
+    prune_cache(app_state.settings.cache_size_limit);
 
-            let mod_count = remote_mod_links.manifests.len();
+    let result =
+        stage_api(release, archive_path.as_path(), staging_path.as_path()).and_then(|_| {
+            replace_api_files(
+                &release.files,
+                staging_path.as_path(),
+                managed_path.as_path(),
+                backup_path.as_path(),
+            )
+        });
+    remove_download(archive_path.as_path());
+    if staging_path.exists() {
+        let _ = fs::remove_dir_all(staging_path.as_path());
+    }
+    result?;
 
-            let mods_path = &state.settings.mods_path;
-            let disabled_path: PathBuf = [mods_path.as_str(), "Disabled"].iter().collect();
-            for i in 0..mod_count {
-                let mod_name = &remote_mod_links.manifests[i].name;
-                let mod_path: PathBuf = [mods_path.clone(), mod_name.clone()].iter().collect();
-                let disabled_mod_path: PathBuf = [
-                    disabled_path.clone().into_os_string().to_str().unwrap(),
-                    mod_name.as_str(),
-                ]
-                .iter()
-                .collect();
-                if mod_path.exists() || disabled_mod_path.exists() {
-                    remote_mod_links.manifests[i].installed = true;
+    print_and_log(format!("Installed the Modding API {}.", release.version));
+    app_state.settings.api_files = release
+        .files
+        .iter()
+        .filter_map(|file| {
+            let local_file = managed_path.join(file.as_str());
+            digest_file(local_file).ok().map(|sha256| InstalledFile {
+                path: file.clone(),
+                sha256: sha256.to_lowercase(),
+            })
+        })
+        .collect();
+    app_state.settings.api_version = release.version.clone();
+    app_state.settings.api_sha256 = release.sha256.to_lowercase();
+    app_state.settings.api_url = release.url.clone();
+
+    Ok(())
+}
+
+/// Extract a downloaded Modding API archive into a fresh staging folder and check that it has
+/// every file of the release
+/// # Arguments
+/// * `release` - The release of the Modding API
+/// * `archive_path` - The path to the downloaded archive
+/// * `staging_path` - The folder to extract the archive into
+fn stage_api(
+    release: &ApiRelease,
+    archive_path: &Path,
+    staging_path: &Path,
+) -> Result<(), DownloadError> {
+    if staging_path.exists() {
+        fs::remove_dir_all(staging_path)
+            .map_err(|e| DownloadError::Io(format!("failed to clear {:?}: {}", staging_path, e)))?;
+    }
+    extract::extract_zip(archive_path, staging_path).map_err(DownloadError::Extract)?;
+    info!("Successfully unzipped API to {:?}.", staging_path);
+
+    // Check that the archive has every file ApiLinks lists before any installed file is touched
+    match release
+        .files
+        .iter()
+        .find(|file| !staging_path.join(file.as_str()).is_file())
+    {
+        Some(file) => Err(DownloadError::Io(format!(
+            "the Modding API archive does not contain {:?}",
+            file
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Move the files of an extracted Modding API into the Managed folder where their hashes differ
+/// from the local files, moving the local files aside first; If any file cannot be moved, the
+/// Managed folder is put back the way it was; Also backs up the vanilla Assembly-CSharp.dll file.
+/// # Arguments
+/// * `files` - The files of the Modding API
+/// * `staging_path` - The folder the Modding API was extracted into
+/// * `managed_path` - The path to the Managed folder
+/// * `backup_path` - The folder to move replaced files into
+fn replace_api_files(
+    files: &[String],
+    staging_path: &Path,
+    managed_path: &Path,
+    backup_path: &Path,
+) -> Result<(), DownloadError> {
+    if backup_path.exists() {
+        fs::remove_dir_all(backup_path)
+            .map_err(|e| DownloadError::Io(format!("failed to clear {:?}: {}", backup_path, e)))?;
+    }
+    let vanilla_backup = managed_path.join("Assembly-CSharp.dll.vanilla");
+    let mut vanilla_backed_up = false;
+    let mut backed_up: Vec<&str> = vec![];
+    let mut moved: Vec<&str> = vec![];
+    let mut result = Ok(());
+    for file in files {
+        let staged_file = staging_path.join(file.as_str());
+        let local_file = managed_path.join(file.as_str());
+        if local_file.exists() {
+            let same = digest_file(staged_file.as_path())
+                .and_then(|staged| {
+                    digest_file(local_file.as_path())
+                        .map(|local| staged.eq_ignore_ascii_case(&local))
+                })
+                .map_err(|e| DownloadError::Io(format!("failed to read {:?}: {}", file, e)));
+            match same {
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(e) => {
+                    result = Err(e);
+                    break;
                 }
-                if mod_path.exists() && !disabled_mod_path.exists() {
-                    remote_mod_links.manifests[i].enabled = true;
+            }
+
+            // An existing backup means the local assembly is an older modded one
+            if file == "Assembly-CSharp.dll" && !vanilla_backup.exists() {
+                if let Err(e) = fs::copy(local_file.as_path(), vanilla_backup.as_path()) {
+                    result = Err(DownloadError::Io(format!(
+                        "failed to back up vanilla Assembly-CSharp: {}",
+                        e
+                    )));
+                    break;
                 }
+                info!("Successfully backed up vanilla Assembly-CSharp.");
+                vanilla_backed_up = true;
+            }
+            if let Err(e) = move_file(
+                local_file.as_path(),
+                backup_path.join(file.as_str()).as_path(),
+            ) {
+                result = Err(DownloadError::Io(format!(
+                    "failed to move {:?} aside: {}",
+                    file, e
+                )));
+                break;
             }
+            backed_up.push(file.as_str());
+        }
 
-            mods_json = serde_json::to_string_pretty(&remote_mod_links).unwrap();
-            state.settings.mod_links = serde_json::from_str(mods_json.as_str()).unwrap();
+        if let Err(e) = move_file(staged_file.as_path(), local_file.as_path()) {
+            result = Err(DownloadError::Io(format!(
+                "failed to move {:?} into the Managed folder: {}",
+                file, e
+            )));
+            break;
+        }
+        info!("Successfully moved API file {:?} to Managed folder.", file);
+        moved.push(file.as_str());
+    }
+
+    if result.is_err() {
+        for file in moved {
+            if let Err(e) = fs::remove_file(managed_path.join(file)) {
+                error!("Failed to remove API file {:?}: {}", file, e);
+            }
+        }
+        for file in backed_up {
+            match move_file(
+                backup_path.join(file).as_path(),
+                managed_path.join(file).as_path(),
+            ) {
+                Ok(_) => info!("Restored {:?} from {:?}", file, backup_path),
+                Err(e) => error!("Failed to restore {:?} from {:?}: {}", file, backup_path, e),
+            }
+        }
+        if vanilla_backed_up {
+            let _ = fs::remove_file(vanilla_backup.as_path());
+        }
+    }
+
+    if backup_path.exists() {
+        if let Err(e) = fs::remove_dir_all(backup_path) {
+            error!(
+                "Failed to remove replaced API files {:?}: {}",
+                backup_path, e
+            );
         }
-        Err(e) => error!("Failed to fetch mod links: {}", e),
     }
+    remove_backup_root(backup_path);
+    result
 }
 
-/// Download a copy of the Modding API and replace local files with its contents if
-/// their hashes do not match; Also backs up the vanilla Assembly-CSharp.dll file.
+/// Move a file, copying it when it has to cross file systems; Creates the folder it is moved into
 /// # Arguments
-/// * `mods_path` - The path to the mods folder
-fn install_api(state: &AppState) {
-    let app_state = state.0.lock().unwrap();
-    let mods_path = &app_state.settings.mods_path;
-    let client = reqwest::blocking::Client::new();
-    let result = client
-        .get(API_URL)
-        .send()
-        .expect("Failed to get response for ApiLinks.");
-    let content = result.text().expect("Failed to get response string.");
-    let mut api_links = ApiLinks::new();
-    match quick_xml::de::from_str(content.as_str()) {
-        Ok(value) => {
-            info!("Successfully parsed API XML.");
-            api_links = value;
-        }
-        Err(e) => error!("Failed to parse API XML: {}", e),
+/// * `from` - The file to move
+/// * `to` - Where to move the file to
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    if let Some(folder) = to.parent() {
+        fs::create_dir_all(folder)?;
     }
+    fs::rename(from, to).or_else(|_| fs::copy(from, to).and_then(|_| fs::remove_file(from)))
+}
 
-    let managed_path: PathBuf = [mods_path.as_str(), ".."].iter().collect();
-    let base_dir = BaseDirs::new().unwrap();
-    let settings_dir: PathBuf = [base_dir.data_dir().to_str().unwrap(), SETTINGS_FOLDER]
+/// Resolve the dependencies of a set of mods and install every mod in the plan that is not
+/// already installed
+/// # Arguments
+/// * `mod_names` - The names of the mods to install
+/// * `state` - The state of the application
+fn install_mods(mod_names: Vec<String>, state: &AppState) -> Result<(), DownloadError> {
+    let manifests: Vec<LocalModManifest> = {
+        let app_state = state.0.lock().unwrap();
+        app_state.settings.mod_links.manifests.clone()
+    };
+    let plan = resolver::resolve(&mod_names, &manifests).map_err(DownloadError::Resolve)?;
+    let explicit: Vec<String> = mod_names
         .iter()
+        .filter_map(|name| resolver::find(name.as_str(), &manifests))
+        .map(|manifest| manifest.name.clone())
         .collect();
-    let temp_path: PathBuf = [
-        settings_dir
-            .into_os_string()
-            .into_string()
-            .unwrap()
-            .as_str(),
-        "..",
-        "Temp",
-    ]
-    .iter()
-    .collect();
-    let api_url: String;
-    match env::consts::OS {
-        "linux" => {
-            api_url =
-                "https://github.com/hk-modding/api/releases/latest/download/ModdingApiLinux.zip"
-                    .to_string()
-        }
-        "mac" => {
-            api_url = "https://github.com/hk-modding/api/releases/latest/download/ModdingApiMac.zip"
-                .to_string()
-        }
-        "windows" => {
-            api_url = "https://github.com/hk-modding/api/releases/latest/download/ModdingApiWin.zip"
-                .to_string()
-        }
-        _ => panic!("OS not supported."),
-    }
 
-    match reqwest::blocking::get(api_url) {
-        Ok(response) => {
-            let content = response.bytes().unwrap();
-            let reader = Cursor::new(content);
-            let unzipper = Unzipper::new(reader, temp_path.clone());
-            match unzipper.unzip() {
-                Ok(_) => info!("Successfully unzipped API to Temp folder."),
-                Err(e) => error!("Failed to unzip API to Temp folder: {}", e),
+    install_plan(plan, &explicit, state)
+}
+
+/// Print an install plan, download every mod in it that is not installed and install the mods
+/// in order, rolling everything back if one of them fails; Mods that are not installed but
+/// still have a folder, such as other versions of them, are replaced, and the old folders are
+/// only deleted once every mod of the plan is installed
+/// # Arguments
+/// * `plan` - The mods to install, each after the mods it depends on
+/// * `explicit` - The names of the mods that were asked for rather than pulled in as dependencies
+/// * `state` - The state of the application
+fn install_plan(
+    plan: Vec<LocalModManifest>,
+    explicit: &[String],
+    state: &AppState,
+) -> Result<(), DownloadError> {
+    println!("Install plan:");
+    for (i, manifest) in plan.iter().enumerate() {
+        println!(
+            "  {}. {} {}{}",
+            i + 1,
+            manifest.name,
+            manifest.version,
+            if manifest.enabled {
+                " (installed)"
+            } else if manifest.installed {
+                " (disabled, will be enabled)"
+            } else {
+                ""
             }
-        }
-        Err(e) => error!("Failed to get response: {}", e),
+        );
     }
 
-    for file in api_links.manifest.files.files {
-        let temp_file: PathBuf = [temp_path.to_str().unwrap(), file.as_str()]
-            .iter()
-            .collect();
-        let local_file: PathBuf = [managed_path.to_str().unwrap(), file.as_str()]
+    let to_download: Vec<LocalModManifest> = plan
+        .iter()
+        .filter(|manifest| !manifest.installed)
+        .cloned()
+        .collect();
+    let (offline, skip_verify) = {
+        let app_state = state.0.lock().unwrap();
+        (app_state.offline, app_state.download_options.skip_verify)
+    };
+    if offline {
+        // Mods can still be installed offline from archives that are already in the cache
+        if let Some(manifest) = to_download
             .iter()
-            .collect();
-        if !local_file.exists() {
-            match fs::rename(temp_file, local_file) {
-                Ok(_) => info!(
-                    "Successfully moved temp file for {:?} to Managed folder.",
-                    file
-                ),
-                Err(e) => error!(
-                    "Failed to move temp file for {:?} to Managed folder: {}",
-                    file, e
-                ),
-            }
-        } else if digest_file(temp_file.clone()).unwrap()
-            != digest_file(local_file.clone()).unwrap()
+            .find(|manifest| skip_verify || !is_cached(manifest.link.sha256.as_str()))
         {
-            if file == "Assembly-CSharp.dll" {
-                let vanilla_backup: PathBuf = [
-                    managed_path.to_str().unwrap(),
-                    "Assembly-CSharp.dll.vanilla",
-                ]
-                .iter()
-                .collect();
-                match fs::rename(local_file.clone(), vanilla_backup) {
-                    Ok(_) => info!("Successfully backed up vanilla Assembly-CSharp."),
-                    Err(e) => error!("Failed to backup vanilla Assembly-Csharp: {}", e),
-                }
-            }
-            match fs::rename(temp_file, local_file) {
-                Ok(_) => info!(
-                    "Successfully replaced old local file for {:?} with new API file.",
-                    file
-                ),
-                Err(e) => error!(
-                    "Failed to replace old local file for {:?} with new API file: {}",
-                    file, e
-                ),
+            return Err(DownloadError::Network(format!(
+                "cannot download mod {:?} in offline mode",
+                manifest.name
+            )));
+        }
+    }
+    let mut downloads = download_mods(&to_download, state)?;
+
+    // Mods are extracted one at a time in plan order, so dependencies always land first
+    let mut steps: Vec<(String, InstallStep)> = vec![];
+    for manifest in plan {
+        let name = manifest.name.clone();
+        // Other versions of the mod are moved aside rather than removed, so that a failure
+        // further down the plan can still put them back
+        let backup = if manifest.installed {
+            Ok(None)
+        } else {
+            back_up_mod(name.as_str(), state)
+        };
+        let result =
+            backup.and_then(
+                |backup| match install_mod(manifest, downloads.remove(&name), state) {
+                    Ok(InstallStep::Installed) => Ok(match backup {
+                        Some(backup) => InstallStep::Replaced(backup),
+                        None => InstallStep::Installed,
+                    }),
+                    Ok(step) => Ok(step),
+                    Err(e) => {
+                        if let Some(backup) = backup {
+                            restore_backup(name.as_str(), backup, state);
+                        }
+                        Err(e)
+                    }
+                },
+            );
+        match result {
+            Ok(step) => steps.push((name.clone(), step)),
+            Err(e) => {
+                downloads.values().for_each(|path| remove_download(path));
+                roll_back(steps, state);
+                return Err(e);
             }
         }
+        if explicit.contains(&name) {
+            let mut app_state = state.0.lock().unwrap();
+            app_state
+                .settings
+                .installed_mods
+                .iter_mut()
+                .filter(|installed| installed.name == name)
+                .for_each(|installed| installed.explicit = true);
+        }
     }
 
-    match fs::remove_dir_all(temp_path) {
-        Ok(_) => info!("Successfully deleted Temp folder."),
-        Err(e) => error!("Failed to delete Temp folder: {}", e),
+    // Only now that every mod of the plan is in place are the replaced versions deleted
+    for (_, step) in steps {
+        if let InstallStep::Replaced(backup) = step {
+            discard_backup(backup);
+        }
     }
+
+    Ok(())
 }
 
-/// Download a mod to disk from a provided link
+/// Move the folder of an installed mod into the backup folder and forget its install record, so
+/// that another version of it can be installed in its place
 /// # Arguments
-/// * `mod_name` - The name of the mod folder to be created
+/// * `mod_name` - The name of the mod
 /// * `state` - The state of the application
-fn install_mod(mut mod_name: String, state: &AppState) {
-    info!("Installing mod {:?}", mod_name);
-
-    let mut mod_link = "".to_string();
+fn back_up_mod(mod_name: &str, state: &AppState) -> Result<Option<Backup>, DownloadError> {
+    let mut app_state = state.0.lock().unwrap();
+    let mods_path = app_state.settings.mods_path.clone();
+    let mod_path: PathBuf = [mods_path.as_str(), mod_name].iter().collect();
+    let disabled_mod_path: PathBuf = [mods_path.as_str(), "Disabled", mod_name].iter().collect();
+    let (original, enabled) = if mod_path.exists() {
+        (mod_path, true)
+    } else if disabled_mod_path.exists() {
+        (disabled_mod_path, false)
+    } else {
+        return Ok(None);
+    };
 
-    let manifests: Vec<LocalModManifest>;
-    {
-        let app_state = state.0.lock().unwrap();
-        manifests = app_state.settings.mod_links.manifests.clone();
+    // The backup folder lives inside the mods folder so moving into it stays on one file system
+    let path: PathBuf = [mods_path.as_str(), BACKUP_FOLDER, mod_name]
+        .iter()
+        .collect();
+    if path.exists() {
+        fs::remove_dir_all(path.as_path())
+            .map_err(|e| DownloadError::Io(format!("failed to clear {:?}: {}", path, e)))?;
+    }
+    if let Some(backup_root) = path.parent() {
+        fs::create_dir_all(backup_root)
+            .map_err(|e| DownloadError::Io(format!("failed to create {:?}: {}", backup_root, e)))?;
     }
+    fs::rename(original.as_path(), path.as_path())
+        .map_err(|e| DownloadError::Io(format!("failed to move {:?} aside: {}", mod_name, e)))?;
+    info!("Moved mod {:?} aside to {:?}", mod_name, path);
 
-    for manifest in manifests {
-        if manifest.name.replace(" ", "").to_lowercase() == mod_name.replace(" ", "").to_lowercase()
-        {
-            mod_name = manifest.name.clone();
-            mod_link = manifest.link.link;
-            manifest
-                .dependencies
-                .dependencies
-                .iter()
-                .for_each(|dependency| {
-                    install_mod(dependency.to_string(), state);
-                });
+    let record = app_state
+        .settings
+        .installed_mods
+        .iter()
+        .position(|installed| installed.name == mod_name)
+        .map(|i| app_state.settings.installed_mods.remove(i));
+    app_state
+        .settings
+        .mod_links
+        .manifests
+        .iter_mut()
+        .filter(|manifest| manifest.name == mod_name)
+        .for_each(|manifest| {
+            manifest.installed = false;
+            manifest.enabled = false;
+        });
+
+    Ok(Some(Backup {
+        original,
+        path,
+        enabled,
+        record,
+    }))
+}
+
+/// Put a mod folder that was moved aside back where it was, along with its install record;
+/// Whatever was installed in its place must already be gone
+/// # Arguments
+/// * `mod_name` - The name of the mod
+/// * `backup` - The moved folder
+/// * `state` - The state of the application
+fn restore_backup(mod_name: &str, backup: Backup, state: &AppState) {
+    if backup.original.exists() {
+        let _ = fs::remove_dir_all(backup.original.as_path());
+    }
+    match fs::rename(backup.path.as_path(), backup.original.as_path()) {
+        Ok(_) => info!("Restored mod {:?} from {:?}", mod_name, backup.path),
+        Err(e) => {
+            error!(
+                "Failed to restore mod {:?} from {:?}: {}",
+                mod_name, backup.path, e
+            );
+            return;
         }
     }
+    remove_backup_root(backup.path.as_path());
 
-    if mod_link == "".to_string() {
-        print_and_log(format!("Mod {:?} not found.", mod_name));
-        return;
+    let mut app_state = state.0.lock().unwrap();
+    app_state
+        .settings
+        .mod_links
+        .manifests
+        .iter_mut()
+        .filter(|manifest| manifest.name == mod_name)
+        .for_each(|manifest| {
+            manifest.installed = true;
+            manifest.enabled = backup.enabled;
+        });
+    app_state
+        .settings
+        .installed_mods
+        .retain(|installed| installed.name != mod_name);
+    if let Some(record) = backup.record {
+        app_state.settings.installed_mods.push(record);
     }
+}
 
-    let mods_path: String;
-    {
-        let mut app_state = state.0.lock().unwrap();
-        (*app_state).current_download_progress = 0;
-        mods_path = app_state.settings.mods_path.clone();
+/// Delete a mod folder that was moved aside once it is no longer needed
+/// # Arguments
+/// * `backup` - The moved folder
+fn discard_backup(backup: Backup) {
+    if let Err(e) = fs::remove_dir_all(backup.path.as_path()) {
+        error!("Failed to remove replaced mod {:?}: {}", backup.path, e);
+    }
+    remove_backup_root(backup.path.as_path());
+}
+
+/// Remove the backup folder once nothing is left in it
+/// # Arguments
+/// * `path` - The path to a mod folder in the backup folder
+fn remove_backup_root(path: &Path) {
+    if let Some(backup_root) = path.parent() {
+        // Only succeeds once no other mod is backed up
+        let _ = fs::remove_dir(backup_root);
+    }
+}
+
+/// Undo the steps of an install plan that failed part way, latest first
+/// # Arguments
+/// * `steps` - The mods of the plan that were handled before the failure and what was done
+/// * `state` - The state of the application
+fn roll_back(steps: Vec<(String, InstallStep)>, state: &AppState) {
+    for (mod_name, step) in steps.into_iter().rev() {
+        match step {
+            InstallStep::Unchanged => continue,
+            InstallStep::Enabled => disable_mod(mod_name.clone(), state),
+            InstallStep::Installed => {
+                uninstall_mod(mod_name.replace(' ', "").to_lowercase(), state)
+            }
+            InstallStep::Replaced(backup) => {
+                uninstall_mod(mod_name.replace(' ', "").to_lowercase(), state);
+                restore_backup(mod_name.as_str(), backup, state);
+            }
+        }
+        print_and_log(format!("Rolled back mod {:?}.", mod_name));
     }
+}
 
+/// Install a downloaded mod into the mods folder; Its dependencies must already be installed
+/// # Arguments
+/// * `manifest` - The manifest of the mod to install
+/// * `download_path` - The path to the downloaded mod, if it is not installed yet
+/// * `state` - The state of the application
+fn install_mod(
+    manifest: LocalModManifest,
+    download_path: Option<PathBuf>,
+    state: &AppState,
+) -> Result<InstallStep, DownloadError> {
+    info!("Installing mod {:?}", manifest.name);
+    let mod_name = manifest.name;
+    let mod_hash = manifest.link.sha256;
+    let mod_url = manifest.link.link;
+    let mod_version = manifest.version;
+
+    let mods_path = state.0.lock().unwrap().settings.mods_path.clone();
     let mod_path: PathBuf = [mods_path.as_str(), mod_name.as_str()].iter().collect();
     let disabled_mod_path: PathBuf = [mods_path.as_str(), "Disabled", mod_name.as_str()]
         .iter()
         .collect();
     if mod_path.exists() {
         warn!("Mod {:?} is already installed and enabled.", mod_name);
-        return;
+        return Ok(InstallStep::Unchanged);
     } else if disabled_mod_path.exists() {
         warn!(
             "Mod {:?} already exists but is disabled, enabling it instead.",
             mod_name
         );
         enable_mod(mod_name.clone(), state);
-        return;
+        return Ok(InstallStep::Enabled);
     }
 
-    let (tx, rx) = mpsc::channel();
-    let tx = tx.clone();
+    let download_path = match download_path {
+        Some(download_path) => download_path,
+        None => {
+            return Err(DownloadError::Io(format!(
+                "mod {:?} was not downloaded",
+                mod_name
+            )))
+        }
+    };
+    extract_mod(
+        mod_name.as_str(),
+        download_path.as_path(),
+        dll_file_name(mod_name.as_str(), mod_url.as_str()).as_str(),
+        mods_path.as_str(),
+    )?;
 
-    let mod_name_param = mod_name.clone();
-    let runtime = Runtime::new().unwrap();
-    runtime.block_on(async move {
-        tokio::spawn(async {
-            download_mod(tx, mod_name_param, mod_link, mods_path).await;
+    print_and_log(format!("Installed mod {:?}.", mod_name));
+    let mut app_state = state.0.lock().unwrap();
+    app_state
+        .settings
+        .mod_links
+        .manifests
+        .iter_mut()
+        .filter(|manifest| manifest.name == mod_name)
+        .for_each(|manifest| {
+            manifest.installed = true;
+            manifest.enabled = true;
         });
+
+    let files = hash_files(mod_path.as_path());
+    app_state
+        .settings
+        .installed_mods
+        .retain(|installed| installed.name != mod_name);
+    app_state.settings.installed_mods.push(InstalledMod {
+        name: mod_name,
+        version: mod_version,
+        sha256: mod_hash.to_lowercase(),
+        url: mod_url,
+        installed_at: unix_time(),
+        files,
+        explicit: false,
     });
 
-    {
-        let mut app_state = state.0.lock().unwrap();
+    Ok(InstallStep::Installed)
+}
 
-        while app_state.current_download_progress < 100 {
-            print!(
-                "Downloading mod {:?}: {}%\r",
-                mod_name, app_state.current_download_progress
-            );
-            std::io::stdout().flush().unwrap();
-            (*app_state).current_download_progress = rx.recv().unwrap();
+/// Install the exact mods and Modding API recorded in a lockfile, replacing installed mods whose
+/// SHA256 differs and disabling every mod that is not in it, then make its profile current
+/// # Arguments
+/// * `lockfile` - The path to the lockfile
+/// * `state` - The state of the application
+fn install_locked(lockfile: &Path, state: &AppState) -> Result<(), String> {
+    let contents = fs::read_to_string(lockfile)
+        .map_err(|e| format!("failed to read {:?}: {}", lockfile, e))?;
+    // The version is checked first, since older lockfiles do not parse as the current format
+    let lockfile_version = serde_json::from_str::<serde_json::Value>(contents.as_str())
+        .map_err(|e| format!("failed to parse {:?}: {}", lockfile, e))?
+        .get("Lockfile Version")
+        .and_then(serde_json::Value::as_u64);
+    if lockfile_version != Some(LOCKFILE_VERSION as u64) {
+        return Err(format!(
+            "{:?} has lockfile version {}, but only version {} is supported; lock the profile \
+             again with this version of hkdl",
+            lockfile,
+            lockfile_version.map_or("unknown".to_string(), |version| version.to_string()),
+            LOCKFILE_VERSION
+        ));
+    }
+    let lock: LockFile = serde_json::from_str(contents.as_str())
+        .map_err(|e| format!("failed to parse {:?}: {}", lockfile, e))?;
+    let mods_path = state.0.lock().unwrap().settings.mods_path.clone();
+    let platform = api_platform(mods_path.as_str()).map_err(|e| e.to_string())?;
+    let api = lock.api.release(platform);
+    if api.url.is_empty() || api.sha256.is_empty() {
+        return Err(format!(
+            "{:?} has no Modding API archive for {}",
+            lockfile, platform
+        ));
+    }
+
+    let plan: Vec<LocalModManifest> = {
+        let app_state = state.0.lock().unwrap();
+        let mods_path = app_state.settings.mods_path.as_str();
+        lock.mods
+            .iter()
+            .map(|locked| {
+                // Mods installed from another archive than the locked one are replaced
+                let matches = app_state.settings.installed_mods.iter().any(|installed| {
+                    installed.name == locked.name
+                        && installed
+                            .sha256
+                            .eq_ignore_ascii_case(locked.sha256.as_str())
+                });
+                let mod_path: PathBuf = [mods_path, locked.name.as_str()].iter().collect();
+                let disabled_mod_path: PathBuf = [mods_path, "Disabled", locked.name.as_str()]
+                    .iter()
+                    .collect();
+                let enabled = matches && mod_path.exists();
+                let installed = enabled || (matches && disabled_mod_path.exists());
+
+                let manifest = app_state
+                    .settings
+                    .mod_links
+                    .manifests
+                    .iter()
+                    .find(|manifest| manifest.name == locked.name)
+                    .cloned()
+                    .unwrap_or_else(|| LocalModManifest {
+                        name: locked.name.clone(),
+                        description: String::new(),
+                        version: String::new(),
+                        link: ModLink {
+                            sha256: String::new(),
+                            link: String::new(),
+                        },
+                        dependencies: ModDependencies {
+                            dependencies: vec![],
+                        },
+                        repository: String::new(),
+                        tags: None,
+                        enabled: false,
+                        installed: false,
+                        source: String::new(),
+                    });
+                LocalModManifest {
+                    version: locked.version.clone(),
+                    link: ModLink {
+                        sha256: locked.sha256.clone(),
+                        link: locked.url.clone(),
+                    },
+                    enabled,
+                    installed,
+                    ..manifest
+                }
+            })
+            .collect()
+    };
+    let explicit: Vec<String> = lock
+        .profile_mods
+        .iter()
+        .map(|profile_mod| profile_mod.name.clone())
+        .collect();
+    install_plan(plan, &explicit, state).map_err(|e| e.to_string())?;
+
+    let manifests = state.0.lock().unwrap().settings.mod_links.manifests.clone();
+    manifests.iter().for_each(|manifest| {
+        if manifest.enabled && !lock.mods.iter().any(|locked| locked.name == manifest.name) {
+            disable_mod(manifest.name.clone(), state);
         }
+    });
+
+    enable_api(Some(&api), state).map_err(|e| e.to_string())?;
+    let api_version = state.0.lock().unwrap().settings.api_version.clone();
+    if api_version != api.version {
+        install_api_release(&api, state).map_err(|e| e.to_string())?;
+    }
+
+    let mut app_state = state.0.lock().unwrap();
+    app_state
+        .settings
+        .profiles
+        .retain(|profile| profile.name != lock.profile);
+    app_state.settings.profiles.push(Profile {
+        name: lock.profile.clone(),
+        mods: lock.profile_mods.clone(),
+    });
+    app_state.settings.current_profile = lock.profile.clone();
+    print_and_log(format!(
+        "Installed {} mods and the Modding API {} from {:?}.",
+        lock.mods.len(),
+        lock.api.version,
+        lockfile
+    ));
+
+    Ok(())
+}
+
+/// Write a lockfile with the exact version, URL and SHA256 of every mod a profile resolves to
+/// and of the latest Modding API
+/// # Arguments
+/// * `profile_name` - The profile to lock, or None for the current profile
+/// * `lockfile` - The path to write the lockfile to
+/// * `state` - The state of the application
+fn lock_profile(
+    profile_name: Option<String>,
+    lockfile: &Path,
+    state: &AppState,
+) -> Result<(), String> {
+    let (profile, manifests, api_links_urls, offline) = {
+        let app_state = state.0.lock().unwrap();
+        let profile_name =
+            profile_name.unwrap_or_else(|| app_state.settings.current_profile.clone());
+        if profile_name.is_empty() {
+            return Err("no profile was given and no profile is selected".to_string());
+        }
+        let profile = app_state
+            .settings
+            .profiles
+            .iter()
+            .find(|profile| profile.name == profile_name)
+            .cloned()
+            .ok_or_else(|| format!("profile {:?} does not exist", profile_name))?;
+        (
+            profile,
+            app_state.settings.mod_links.manifests.clone(),
+            app_state.api_links_urls.clone(),
+            app_state.offline,
+        )
+    };
+
+    let mod_names: Vec<String> = profile
+        .mods
+        .iter()
+        .map(|profile_mod| profile_mod.name.clone())
+        .collect();
+    let plan = resolver::resolve(&mod_names, &manifests).map_err(|e| e.to_string())?;
+    let pins = profile_pins(&profile);
+    for manifest in plan.iter() {
+        if let Some(pin) = pin_for(&pins, manifest.name.as_str()) {
+            if !pin.matches(manifest.version.as_str()) {
+                return Err(format!(
+                    "mod {:?} is pinned to {}, but ModLinks only offers version {}",
+                    manifest.name, pin, manifest.version
+                ));
+            }
+        }
+    }
+    let api = latest_api(&api_links_urls, offline).map_err(|e| e.to_string())?;
+    let lock = LockFile {
+        lockfile_version: LOCKFILE_VERSION,
+        profile: profile.name.clone(),
+        profile_mods: profile
+            .mods
+            .iter()
+            .filter_map(|profile_mod| {
+                resolver::find(profile_mod.name.as_str(), &manifests).map(|manifest| ProfileMod {
+                    name: manifest.name.clone(),
+                    pin: profile_mod.pin.clone(),
+                })
+            })
+            .collect(),
+        api,
+        mods: plan
+            .iter()
+            .map(|manifest| LockedMod {
+                name: manifest.name.clone(),
+                version: manifest.version.clone(),
+                url: manifest.link.link.clone(),
+                sha256: manifest.link.sha256.to_lowercase(),
+            })
+            .collect(),
+    };
+
+    let contents = serde_json::to_string_pretty(&lock).map_err(|e| e.to_string())?;
+    fs::write(lockfile, contents).map_err(|e| format!("failed to write {:?}: {}", lockfile, e))?;
+    println!(
+        "Locked profile {:?} to the Modding API {} and {} mods in {:?}:",
+        lock.profile,
+        lock.api.version,
+        lock.mods.len(),
+        lockfile
+    );
+    for locked in lock.mods.iter() {
+        println!("  {} {}", locked.name, locked.version);
+    }
+
+    Ok(())
+}
 
-        println!(
-            "Downloading mod {:?}: {}%!",
-            mod_name, app_state.current_download_progress
-        );
+/// Read the contents of a file from either a remote URL or a local file:// path
+/// # Arguments
+/// * `url` - The URL or path of the file
+fn read_url(url: &str) -> Result<String, String> {
+    match local_path(url) {
+        Some(path) => fs::read_to_string(path).map_err(|e| e.to_string()),
+        None => {
+            let client = reqwest::blocking::Client::new();
+            client
+                .get(url)
+                .send()
+                .and_then(|response| response.error_for_status())
+                .and_then(|response| response.text())
+                .map_err(|e| e.to_string())
+        }
+    }
+}
 
-        for i in 0..app_state.settings.mod_links.manifests.len() {
-            if app_state.settings.mod_links.manifests[i].name == mod_name {
-                app_state.settings.mod_links.manifests[i].installed = true;
-                app_state.settings.mod_links.manifests[i].enabled = true;
-            }
+/// Get the local path that a URL points to, if it is a file:// URL or has no scheme at all
+/// # Arguments
+/// * `url` - The URL to check
+fn local_path(url: &str) -> Option<PathBuf> {
+    match url.strip_prefix("file://") {
+        Some(path) => {
+            // file:///C:/... on Windows
+            let path = match path.strip_prefix('/') {
+                Some(rest) if rest.chars().nth(1) == Some(':') => rest,
+                _ => path,
+            };
+            Some(PathBuf::from(path))
         }
+        None if !url.contains("://") => Some(PathBuf::from(url)),
+        None => None,
     }
 }
 
-/// Parse arguments passed to application
+/// Parse arguments passed to application and return the exit code of the app
 /// # Arguments
+/// * `args` - The arguments passed to the application
 /// * `state` - The state of the application
-fn parse_args(state: &AppState) {
-    let args = Arguments::parse();
+fn parse_args(args: Arguments, state: &AppState) -> i32 {
+    let profile_name = args.profile_name.clone();
     match args.cmd {
-        SubCommand::Add { mut query } => {
+        SubCommand::Add { mut query, mods } => {
             query = query.replace(" ", "").to_lowercase();
+            if mods.is_some() && query != "profile" {
+                eprintln!("Error: --mods can only be used with `add profile`");
+                error!("--mods was passed when adding {:?}", query);
+                return 1;
+            }
             if query == "api" {
-                enable_api(state);
+                if let Err(e) = enable_api(None, state) {
+                    eprintln!("Error: {}", e);
+                    error!("Failed to enable the Modding API: {}", e);
+                    return 1;
+                }
             } else if query == "profile" {
-                create_profile(state);
-            } else {
-                install_mod(query, state);
+                if let Err(e) = create_profile(profile_name, mods, state) {
+                    eprintln!("Error: {}", e);
+                    error!("Failed to create profile: {}", e);
+                    return 1;
+                }
+            } else if let Err(e) = install_mods(vec![query], state) {
+                eprintln!("Error: {}", e);
+                error!("Failed to install mod: {}", e);
+                return 1;
             }
         }
         SubCommand::Info { mut query } => {
             let app_state = state.0.lock().unwrap();
             query = query.replace(" ", "").to_lowercase();
+            if query == "api" {
+                let vanilla_assembly: PathBuf = [
+                    app_state.settings.mods_path.as_str(),
+                    "..",
+                    "Assembly-CSharp.dll.vanilla",
+                ]
+                .iter()
+                .collect();
+                let installed_version = &app_state.settings.api_version;
+                println!("Mod:\t\tModding API");
+                match fetch_api_links(&app_state.api_links_urls, app_state.offline) {
+                    Some(api_links) => {
+                        println!("Version:\t{}", api_links.manifest.version);
+                        if let Ok(platform) = api_platform(app_state.settings.mods_path.as_str()) {
+                            let api_link = api_links.manifest.links.get(platform);
+                            println!("Link:\t\t{}", api_link.link);
+                            println!("SHA256:\t\t{}", api_link.sha256);
+                        }
+                        if !installed_version.is_empty()
+                            && version::is_outdated(
+                                installed_version.as_str(),
+                                api_links.manifest.version.as_str(),
+                            )
+                        {
+                            println!("\t\tUpdate available: {}", api_links.manifest.version);
+                        }
+                    }
+                    None => println!("Version:\tunknown"),
+                }
+                println!("Enabled:\t{}", vanilla_assembly.exists());
+                if installed_version.is_empty() {
+                    println!("Installed:\tfalse");
+                } else {
+                    println!("Installed:\t{}", installed_version);
+                }
+                return 0;
+            }
             app_state
                 .settings
                 .mod_links
@@ -1024,7 +2866,8 @@ fn parse_args(state: &AppState) {
                         println!("Version:\t{}", manifest.version);
                         println!("SHA256:\t\t{}", manifest.link.sha256);
                         println!("Repository:\t{}", manifest.repository);
-                        if manifest.dependencies.dependencies.len() > 0 {
+                        println!("Source:\t\t{}", manifest.source);
+                        if !manifest.dependencies.dependencies.is_empty() {
                             println!("Dependencies:");
                             manifest
                                 .dependencies
@@ -1046,20 +2889,64 @@ fn parse_args(state: &AppState) {
                             None => println!("Tags:\t\tNone"),
                         }
                         println!("Enabled:\t{}", manifest.enabled);
-                        println!("Installed:\t{}", manifest.installed);
+                        match app_state
+                            .settings
+                            .installed_mods
+                            .iter()
+                            .find(|installed| installed.name == manifest.name)
+                        {
+                            Some(installed) => {
+                                println!(
+                                    "Installed:\t{} on {}{}",
+                                    installed.version,
+                                    format_timestamp(installed.installed_at),
+                                    if installed.explicit {
+                                        ""
+                                    } else {
+                                        " as a dependency"
+                                    }
+                                );
+                                if version::is_outdated(
+                                    installed.version.as_str(),
+                                    manifest.version.as_str(),
+                                ) {
+                                    println!("\t\tUpdate available: {}", manifest.version);
+                                }
+                            }
+                            None if manifest.installed => {
+                                println!("Installed:\ttrue (unknown version)")
+                            }
+                            None => println!("Installed:\tfalse"),
+                        }
                     }
                 });
 
-            match app_state.settings.profiles.iter().find(|profile| profile.name.trim().to_lowercase() == query) {
+            match app_state
+                .settings
+                .profiles
+                .iter()
+                .find(|profile| profile.name.trim().to_lowercase() == query)
+            {
                 Some(profile) => {
                     println!("Profile: {}", profile.name);
-                    profile.mods.iter().for_each(|mod_name| {
-                        println!("\t- {}", mod_name);
-                    });
+                    profile
+                        .mods
+                        .iter()
+                        .for_each(|profile_mod| match &profile_mod.pin {
+                            Some(pin) => println!("\t- {} (pinned to {})", profile_mod.name, pin),
+                            None => println!("\t- {}", profile_mod.name),
+                        });
                 }
                 None => error!("Failed to find a profile of the name {:?}", query),
             }
         }
+        SubCommand::InstallDirs { cmd } => {
+            if let Err(e) = manage_install_dirs(cmd, state) {
+                eprintln!("Error: {}", e);
+                error!("Failed to manage installations: {}", e);
+                return 1;
+            }
+        }
         SubCommand::List { filter } => {
             let app_state = state.0.lock().unwrap();
             match filter {
@@ -1079,9 +2966,37 @@ fn parse_args(state: &AppState) {
                             .mod_links
                             .manifests
                             .iter()
+                            .filter(|manifest| manifest.installed)
                             .for_each(|manifest| {
-                                if manifest.installed {
-                                    println!("{}", manifest.name);
+                                match app_state
+                                    .settings
+                                    .installed_mods
+                                    .iter()
+                                    .find(|installed| installed.name == manifest.name)
+                                {
+                                    Some(installed)
+                                        if version::is_outdated(
+                                            installed.version.as_str(),
+                                            manifest.version.as_str(),
+                                        ) =>
+                                    {
+                                        println!(
+                                            "{} ({} -> {})",
+                                            display_name(manifest),
+                                            installed.version,
+                                            manifest.version
+                                        )
+                                    }
+                                    Some(installed) => {
+                                        println!(
+                                            "{} ({})",
+                                            display_name(manifest),
+                                            installed.version
+                                        )
+                                    }
+                                    None => {
+                                        println!("{} (unknown version)", display_name(manifest))
+                                    }
                                 }
                             });
                     }
@@ -1093,7 +3008,7 @@ fn parse_args(state: &AppState) {
                             .iter()
                             .for_each(|manifest| {
                                 if manifest.enabled {
-                                    println!("{}", manifest.name);
+                                    println!("{}", display_name(manifest));
                                 }
                             });
                     }
@@ -1105,7 +3020,7 @@ fn parse_args(state: &AppState) {
                             .iter()
                             .for_each(|manifest| {
                                 if !manifest.enabled && manifest.installed {
-                                    println!("{}", manifest.name);
+                                    println!("{}", display_name(manifest));
                                 }
                             });
                     }
@@ -1117,7 +3032,7 @@ fn parse_args(state: &AppState) {
                             .iter()
                             .for_each(|manifest| {
                                 if !manifest.installed {
-                                    println!("{}", manifest.name);
+                                    println!("{}", display_name(manifest));
                                 }
                             });
                     }
@@ -1129,7 +3044,7 @@ fn parse_args(state: &AppState) {
                             .iter()
                             .for_each(|manifest| {
                                 if manifest.name.to_lowercase().contains(filter.as_str()) {
-                                    println!("{}", manifest.name);
+                                    println!("{}", display_name(manifest));
                                 }
                             });
                     }
@@ -1141,12 +3056,57 @@ fn parse_args(state: &AppState) {
                         .manifests
                         .iter()
                         .for_each(|manifest| {
-                            println!("{}", manifest.name);
+                            println!("{}", display_name(manifest));
                         });
                 }
             }
         }
-        SubCommand::Rm { mut query } => {
+        SubCommand::Autoremove { dry_run } => autoremove(dry_run, state),
+        SubCommand::Cache { cmd } => manage_cache(cmd, state),
+        SubCommand::Install { locked, lockfile } => {
+            let result = if locked {
+                install_locked(lockfile.as_path(), state)
+            } else {
+                let profile_name = state.0.lock().unwrap().settings.current_profile.clone();
+                if profile_name.is_empty() {
+                    Err("no profile is selected".to_string())
+                } else {
+                    apply_profile(profile_name.as_str(), state)
+                        .and_then(|_| enable_api(None, state).map_err(|e| e.to_string()))
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("Error: {}", e);
+                error!("Failed to install: {}", e);
+                return 1;
+            }
+        }
+        SubCommand::Lock { profile, lockfile } => {
+            if let Err(e) = lock_profile(profile, lockfile.as_path(), state) {
+                eprintln!("Error: {}", e);
+                error!("Failed to write lockfile: {}", e);
+                return 1;
+            }
+        }
+        SubCommand::Outdated => {
+            if print_outdated(state) {
+                return 1;
+            }
+        }
+        SubCommand::Pin {
+            query,
+            constraint,
+            profile,
+            remove,
+        } => {
+            let constraint = if remove { None } else { Some(constraint) };
+            if let Err(e) = pin_mod(query, constraint, profile, state) {
+                eprintln!("Error: {}", e);
+                error!("Failed to pin mod: {}", e);
+                return 1;
+            }
+        }
+        SubCommand::Rm { mut query, cascade } => {
             query = query.replace(" ", "").to_lowercase();
             match query.as_str() {
                 "*" => {
@@ -1166,36 +3126,752 @@ fn parse_args(state: &AppState) {
                     disable_api(state);
                 }
                 "profile" => {
-                    delete_profile(state);
+                    if let Err(e) = delete_profile(profile_name, state) {
+                        eprintln!("Error: {}", e);
+                        error!("Failed to delete profile: {}", e);
+                        return 1;
+                    }
                 }
                 _ => {
-                    uninstall_mod(query, state);
+                    if !remove_mod(query, cascade, state) {
+                        return 1;
+                    }
+                }
+            }
+        }
+        SubCommand::SetPath { path } => {
+            let mut app_state = state.0.lock().unwrap();
+            if let Err(e) = set_game_path(&mut app_state, path.as_str()) {
+                eprintln!("Error: {}", e);
+                error!("Failed to set game path: {}", e);
+                return 1;
+            }
+        }
+        SubCommand::Status => print_status(state),
+        SubCommand::Update { query, all } => {
+            let result = if all {
+                update_api(state).and_then(|_| update_all_mods(state))
+            } else {
+                let query = query.unwrap_or_default().replace(' ', "").to_lowercase();
+                match query.as_str() {
+                    "profile" => {
+                        if let Err(e) = set_profile(profile_name, state) {
+                            eprintln!("Error: {}", e);
+                            error!("Failed to switch profile: {}", e);
+                            return 1;
+                        }
+                        Ok(())
+                    }
+                    "api" => update_api(state),
+                    _ => update_mod(query, state),
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("Error: {}", e);
+                error!("Failed to update: {}", e);
+                return 1;
+            }
+        }
+        SubCommand::Verify { query, repair } => {
+            if !verify(query, repair, state) {
+                return 1;
+            }
+        }
+    }
+
+    0
+}
+
+/// Compare the files in a folder with the files recorded when it was installed; Returns the
+/// (modified, missing, extra) files
+/// # Arguments
+/// * `root` - The folder the files were installed to
+/// * `files` - The files that were installed
+/// * `check_extra` - Whether files in the folder that were not installed count as extra
+fn compare_files(
+    root: &Path,
+    files: &[InstalledFile],
+    check_extra: bool,
+) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let mut modified = vec![];
+    let mut missing = vec![];
+    for file in files {
+        let path = root.join(file.path.as_str());
+        match digest_file(path.as_path()) {
+            Err(_) if !path.exists() => missing.push(file.path.clone()),
+            // Files recorded before hashes were kept can only be checked for being there
+            Ok(_) if file.sha256.is_empty() => {}
+            Ok(sha256) if sha256.eq_ignore_ascii_case(file.sha256.as_str()) => {}
+            _ => modified.push(file.path.clone()),
+        }
+    }
+
+    let extra = if check_extra {
+        list_files(root)
+            .into_iter()
+            .filter(|path| !files.iter().any(|file| &file.path == path))
+            .collect()
+    } else {
+        vec![]
+    };
+
+    (modified, missing, extra)
+}
+
+/// Print how the files of an installation differ from what was installed; Returns whether they
+/// all match
+/// # Arguments
+/// * `name` - The name of the installation
+/// * `differences` - The (modified, missing, extra) files
+fn print_differences(name: &str, differences: &(Vec<String>, Vec<String>, Vec<String>)) -> bool {
+    let (modified, missing, extra) = differences;
+    if modified.is_empty() && missing.is_empty() && extra.is_empty() {
+        println!("{}: OK", name);
+        return true;
+    }
+
+    println!(
+        "{}: {} modified, {} missing, {} extra",
+        name,
+        modified.len(),
+        missing.len(),
+        extra.len()
+    );
+    modified
+        .iter()
+        .for_each(|path| println!("\tmodified:\t{}", path));
+    missing
+        .iter()
+        .for_each(|path| println!("\tmissing:\t{}", path));
+    extra
+        .iter()
+        .for_each(|path| println!("\textra:\t\t{}", path));
+    false
+}
+
+/// Check the files of installed mods and the Modding API against the ones recorded when they
+/// were installed, optionally reinstalling the ones that do not match; Returns whether
+/// everything matches or was repaired
+/// # Arguments
+/// * `query` - The mod to check, or "api"; Everything is checked if it is None
+/// * `repair` - Whether to reinstall what does not match
+/// * `state` - The state of the application
+fn verify(query: Option<String>, repair: bool, state: &AppState) -> bool {
+    let query = query.map(|query| query.replace(' ', "").to_lowercase());
+    let (mods_path, manifests, installed_mods, api_files) = {
+        let app_state = state.0.lock().unwrap();
+        (
+            app_state.settings.mods_path.clone(),
+            app_state.settings.mod_links.manifests.clone(),
+            app_state.settings.installed_mods.clone(),
+            app_state.settings.api_files.clone(),
+        )
+    };
+
+    let mut broken_mods = vec![];
+    let mut api_broken = false;
+    if query.is_none() || query.as_deref() == Some("api") {
+        let managed_path: PathBuf = [mods_path.as_str(), ".."].iter().collect();
+        if !managed_path.join("Assembly-CSharp.dll.vanilla").exists() {
+            println!("Modding API: not installed and enabled, skipping");
+        } else if api_files.is_empty() {
+            println!("Modding API: no record of its files, update it to start tracking them");
+        } else {
+            let differences = compare_files(managed_path.as_path(), &api_files, false);
+            api_broken = !print_differences("Modding API", &differences);
+        }
+    }
+
+    let mods_to_check: Vec<&LocalModManifest> = match query.as_deref() {
+        None => manifests
+            .iter()
+            .filter(|manifest| manifest.installed)
+            .collect(),
+        Some("api") => vec![],
+        Some(query) => match resolver::find(query, &manifests) {
+            Some(manifest) if manifest.installed => vec![manifest],
+            _ => {
+                eprintln!("Error: mod {:?} is not installed", query);
+                return false;
+            }
+        },
+    };
+    for manifest in mods_to_check {
+        let installed = match installed_mods
+            .iter()
+            .find(|installed| installed.name == manifest.name)
+        {
+            Some(installed) if !installed.files.is_empty() => installed,
+            _ => {
+                println!(
+                    "{}: no record of its files, reinstall it to start tracking them",
+                    manifest.name
+                );
+                continue;
+            }
+        };
+        let mod_path: PathBuf = [mods_path.as_str(), manifest.name.as_str()]
+            .iter()
+            .collect();
+        let disabled_mod_path: PathBuf = [mods_path.as_str(), "Disabled", manifest.name.as_str()]
+            .iter()
+            .collect();
+        let root = if mod_path.exists() {
+            mod_path
+        } else {
+            disabled_mod_path
+        };
+        let differences = compare_files(root.as_path(), &installed.files, true);
+        if !print_differences(manifest.name.as_str(), &differences) {
+            broken_mods.push(manifest.name.clone());
+        }
+    }
+
+    if broken_mods.is_empty() && !api_broken {
+        return true;
+    }
+    if !repair {
+        println!("Run with --repair to reinstall what does not match.");
+        return false;
+    }
+
+    let mut repaired = true;
+    if api_broken {
+        match repair_api(state) {
+            Ok(_) => print_and_log("Repaired the Modding API.".to_string()),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                error!("Failed to repair the Modding API: {}", e);
+                repaired = false;
+            }
+        }
+    }
+    for mod_name in broken_mods {
+        match repair_mod(mod_name.clone(), state) {
+            Ok(_) => print_and_log(format!("Repaired mod {:?}.", mod_name)),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                error!("Failed to repair mod {:?}: {}", mod_name, e);
+                repaired = false;
+            }
+        }
+    }
+    repaired
+}
+
+/// Reinstall the files of the Modding API from the archive it was installed from
+/// # Arguments
+/// * `state` - The state of the application
+fn repair_api(state: &AppState) -> Result<(), DownloadError> {
+    let release = {
+        let app_state = state.0.lock().unwrap();
+        let settings = &app_state.settings;
+        ApiRelease {
+            version: settings.api_version.clone(),
+            url: settings.api_url.clone(),
+            sha256: settings.api_sha256.clone(),
+            files: settings
+                .api_files
+                .iter()
+                .map(|file| file.path.clone())
+                .collect(),
+        }
+    };
+    if release.url.is_empty() || release.sha256.is_empty() {
+        return Err(DownloadError::Io(
+            "there is no record of the archive the Modding API was installed from; update it to \
+             start tracking it"
+                .to_string(),
+        ));
+    }
+
+    install_api_release(&release, state)
+}
+
+/// Reinstall a mod from the archive it was installed from, keeping whether it is enabled and
+/// whether it was asked for; Its dependencies are left as they are
+/// # Arguments
+/// * `mod_name` - The name of the mod
+/// * `state` - The state of the application
+fn repair_mod(mod_name: String, state: &AppState) -> Result<(), DownloadError> {
+    let (manifest, installed) = {
+        let app_state = state.0.lock().unwrap();
+        let manifest = app_state
+            .settings
+            .mod_links
+            .manifests
+            .iter()
+            .find(|manifest| manifest.name == mod_name)
+            .cloned();
+        let installed = app_state
+            .settings
+            .installed_mods
+            .iter()
+            .find(|installed| installed.name == mod_name)
+            .cloned();
+        match (manifest, installed) {
+            (Some(manifest), Some(installed)) => (manifest, installed),
+            _ => {
+                return Err(DownloadError::Io(format!(
+                    "mod {:?} is not installed",
+                    mod_name
+                )))
+            }
+        }
+    };
+    if installed.url.is_empty() || installed.sha256.is_empty() {
+        return Err(DownloadError::Io(format!(
+            "there is no record of the archive mod {:?} was installed from; reinstall it to \
+             start tracking it",
+            mod_name
+        )));
+    }
+
+    // The broken files are only replaced once the recorded archive is downloaded and in place,
+    // and are put back if that fails
+    let plan = vec![LocalModManifest {
+        version: installed.version.clone(),
+        link: ModLink {
+            sha256: installed.sha256.clone(),
+            link: installed.url.clone(),
+        },
+        installed: false,
+        enabled: false,
+        ..manifest.clone()
+    }];
+    install_plan(plan, &[], state)?;
+    {
+        let mut app_state = state.0.lock().unwrap();
+        app_state
+            .settings
+            .installed_mods
+            .iter_mut()
+            .filter(|record| record.name == mod_name)
+            .for_each(|record| record.explicit = installed.explicit);
+    }
+    if !manifest.enabled {
+        disable_mod(mod_name, state);
+    }
+
+    Ok(())
+}
+
+/// Print the installation in use, the version of the game in it and the state of its Modding
+/// API and mods
+/// # Arguments
+/// * `state` - The state of the application
+fn print_status(state: &AppState) {
+    let (install_dir, mods_path, api_version, api_url, current_profile, manifests) = {
+        let app_state = state.0.lock().unwrap();
+        (
+            app_state.install_dir.clone().unwrap_or_default(),
+            app_state.settings.mods_path.clone(),
+            app_state.settings.api_version.clone(),
+            app_state.settings.api_url.clone(),
+            app_state.settings.current_profile.clone(),
+            app_state.settings.mod_links.manifests.clone(),
+        )
+    };
+    let managed_path = Path::new(mods_path.as_str())
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let game_path = detect::game_path(managed_path.as_path());
+
+    println!("Installation: {}", install_dir);
+    if let Some(game_path) = &game_path {
+        println!("Game path: {}", game_path.display());
+    }
+    println!("Mods path: {}", mods_path);
+    let build_id = game_path
+        .as_ref()
+        .and_then(|game_path| steam::build_id(game_path.as_path()));
+    match (game::game_version(managed_path.as_path()), build_id) {
+        (Some(game_version), Some(build_id)) => {
+            println!("Game version: {} (Steam build {})", game_version, build_id)
+        }
+        (Some(game_version), None) => println!("Game version: {}", game_version),
+        (None, Some(build_id)) => println!("Game version: unknown (Steam build {})", build_id),
+        (None, None) => println!("Game version: unknown"),
+    }
+
+    let api_state = if managed_path.join("Assembly-CSharp.dll.vanilla").exists() {
+        "enabled"
+    } else if managed_path.join("Assembly-CSharp.dll.modded").exists() {
+        "disabled"
+    } else {
+        "not installed"
+    };
+    if api_version.is_empty() || api_state == "not installed" {
+        println!("Modding API: {}", api_state);
+    } else {
+        match game::api_game_version(api_url.as_str()) {
+            Some(target) => println!(
+                "Modding API: {} ({}, targets game version {})",
+                api_version, api_state, target
+            ),
+            None => println!("Modding API: {} ({})", api_version, api_state),
+        }
+        warn_api_target(api_version.as_str(), api_url.as_str(), mods_path.as_str());
+    }
+
+    let installed = manifests
+        .iter()
+        .filter(|manifest| manifest.installed)
+        .count();
+    let enabled = manifests.iter().filter(|manifest| manifest.enabled).count();
+    println!("Mods: {} installed, {} enabled", installed, enabled);
+    println!(
+        "Profile: {}",
+        if current_profile.is_empty() {
+            "none"
+        } else {
+            current_profile.as_str()
+        }
+    );
+}
+
+/// Warn if a release of the Modding API targets a different version of the game than the one
+/// installed; Nothing is printed if either version cannot be read
+/// # Arguments
+/// * `api_version` - The version of the Modding API, such as `77`
+/// * `api_url` - The download link of the Modding API release
+/// * `mods_path` - The path to the mods folder of the game
+fn warn_api_target(api_version: &str, api_url: &str, mods_path: &str) {
+    let target = match game::api_game_version(api_url) {
+        Some(target) => target,
+        None => return,
+    };
+    let game_version = Path::new(mods_path).parent().and_then(game::game_version);
+    if let Some(game_version) = game_version {
+        if version::compare(target, game_version.as_str()) != std::cmp::Ordering::Equal {
+            print_and_log(format!(
+                "Warning: the Modding API {} targets game version {}, but version {} is installed.",
+                api_version, target, game_version
+            ));
+        }
+    }
+}
+
+/// Print a table of installed mods whose versions are behind ModLinks, followed by the mods
+/// whose installed versions are unknown and the mods that are new since `outdated` last ran;
+/// Returns whether anything is outdated
+/// # Arguments
+/// * `state` - The state of the application
+fn print_outdated(state: &AppState) -> bool {
+    let (manifests, installed_mods, new_mods, api_version, api_links_urls, offline) = {
+        let mut app_state = state.0.lock().unwrap();
+        let names: Vec<String> = app_state
+            .settings
+            .mod_links
+            .manifests
+            .iter()
+            .map(|manifest| manifest.name.clone())
+            .collect();
+        // Nothing counts as new the first time, as there is nothing to compare with
+        let new_mods: Vec<String> = if app_state.settings.seen_mods.is_empty() {
+            vec![]
+        } else {
+            names
+                .iter()
+                .filter(|name| !app_state.settings.seen_mods.contains(name))
+                .cloned()
+                .collect()
+        };
+        if !names.is_empty() {
+            app_state.settings.seen_mods = names;
+        }
+        (
+            app_state.settings.mod_links.manifests.clone(),
+            app_state.settings.installed_mods.clone(),
+            new_mods,
+            app_state.settings.api_version.clone(),
+            app_state.api_links_urls.clone(),
+            app_state.offline,
+        )
+    };
+
+    let mut rows: Vec<(String, String, String)> = vec![];
+    if !api_version.is_empty() {
+        if let Some(api_links) = fetch_api_links(&api_links_urls, offline) {
+            if version::is_outdated(api_version.as_str(), api_links.manifest.version.as_str()) {
+                rows.push((
+                    "Modding API".to_string(),
+                    api_version,
+                    api_links.manifest.version,
+                ));
+            }
+        }
+    }
+    // Mods installed without a record, e.g. by hand, cannot be compared
+    let mut unknown: Vec<(String, String)> = vec![];
+    for manifest in manifests.iter().filter(|manifest| manifest.installed) {
+        match installed_mods
+            .iter()
+            .find(|installed| installed.name == manifest.name)
+            .map(|installed| installed.version.as_str())
+        {
+            Some(installed_version) if !installed_version.is_empty() => {
+                if version::is_outdated(installed_version, manifest.version.as_str()) {
+                    rows.push((
+                        manifest.name.clone(),
+                        installed_version.to_string(),
+                        manifest.version.clone(),
+                    ));
                 }
             }
+            _ => unknown.push((manifest.name.clone(), manifest.version.clone())),
+        }
+    }
+
+    if rows.is_empty() && unknown.is_empty() {
+        println!("All installed mods are up to date.");
+    } else if rows.is_empty() {
+        println!("All installed mods with known versions are up to date.");
+    } else {
+        let name_width = rows
+            .iter()
+            .map(|row| row.0.len())
+            .max()
+            .unwrap_or(0)
+            .max("Mod".len());
+        let installed_width = rows
+            .iter()
+            .map(|row| row.1.len())
+            .max()
+            .unwrap_or(0)
+            .max("Installed".len());
+        println!(
+            "{:name_width$}  {:installed_width$}  Available",
+            "Mod",
+            "Installed",
+            name_width = name_width,
+            installed_width = installed_width
+        );
+        for (name, installed, available) in rows.iter() {
+            println!(
+                "{:name_width$}  {:installed_width$}  {}",
+                name,
+                installed,
+                available,
+                name_width = name_width,
+                installed_width = installed_width
+            );
+        }
+    }
+
+    if !unknown.is_empty() {
+        println!();
+        println!("Installed mods whose versions are unknown:");
+        unknown.iter().for_each(|(mod_name, available)| {
+            println!("\t- {} (available: {})", mod_name, available)
+        });
+    }
+
+    if !new_mods.is_empty() {
+        println!();
+        println!("New mods since outdated last ran:");
+        new_mods
+            .iter()
+            .for_each(|mod_name| println!("\t- {}", mod_name));
+    }
+
+    !rows.is_empty()
+}
+
+/// Ask the user a yes or no question until they give a valid answer; Returns false if no
+/// answer can be read
+/// # Arguments
+/// * `question` - The question to ask
+/// * `assume_yes` - Whether to answer yes without asking
+fn confirm(question: &str, assume_yes: bool) -> bool {
+    if assume_yes {
+        println!("{} [y/n] y", question);
+        return true;
+    }
+    loop {
+        print!("{} [y/n] ", question);
+        io::stdout().flush().unwrap();
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(0) | Err(_) => {
+                println!();
+                return false;
+            }
+            Ok(_) => {}
+        }
+        match input.trim().to_lowercase().as_str() {
+            "y" => return true,
+            "n" => return false,
+            _ => println!("This is not a valid input. Please enter 'y' for 'yes' or 'n' for 'no'."),
+        }
+    }
+}
+
+/// Ask the user for a line of input and return it trimmed; Fails with the given message instead
+/// of asking when prompts are turned off, or when no input can be read
+/// # Arguments
+/// * `question` - The prompt to print
+/// * `missing` - The error to fail with, telling the user how to pass the input instead
+/// * `non_interactive` - Whether prompts are turned off
+fn prompt(question: &str, missing: &str, non_interactive: bool) -> Result<String, String> {
+    if non_interactive {
+        return Err(missing.to_string());
+    }
+    print!("{}", question);
+    io::stdout().flush().unwrap();
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+        Ok(0) | Err(_) => {
+            println!();
+            Err(missing.to_string())
+        }
+        Ok(_) => Ok(input.trim().to_string()),
+    }
+}
+
+fn print_and_log(message: String) {
+    println!("{}", message);
+    info!("{}", message);
+}
+
+/// The name of a mod as shown in lists, labelled with its source if it is not the official one
+/// # Arguments
+/// * `manifest` - The manifest of the mod
+fn display_name(manifest: &LocalModManifest) -> String {
+    if manifest.source.is_empty() || manifest.source == OFFICIAL_SOURCE {
+        manifest.name.clone()
+    } else {
+        format!("{} [{}]", manifest.name, manifest.source)
+    }
+}
+
+/// Format a duration in seconds as a rough human-readable age, e.g. `3 days`
+/// # Arguments
+/// * `seconds` - The duration in seconds
+fn format_age(seconds: u64) -> String {
+    let (amount, unit) = match seconds {
+        0..=59 => (seconds, "second"),
+        60..=3599 => (seconds / 60, "minute"),
+        3600..=86399 => (seconds / 3600, "hour"),
+        _ => (seconds / 86400, "day"),
+    };
+    format!("{} {}{}", amount, unit, if amount == 1 { "" } else { "s" })
+}
+
+/// Format a size in bytes with a binary unit, e.g. `1.5 MiB`
+/// # Arguments
+/// * `bytes` - The size in bytes
+fn format_size(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, units[0])
+    } else {
+        format!("{:.1} {}", size, units[unit])
+    }
+}
+
+/// Format a Unix timestamp as a UTC date and time, e.g. `2022-10-05 14:03 UTC`
+/// # Arguments
+/// * `timestamp` - Seconds since the Unix epoch
+fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+    // Convert days since the epoch to a civil date
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        (seconds % 3600) / 60
+    )
+}
+
+/// Recursively list the files under a folder as paths relative to that folder
+/// # Arguments
+/// * `root` - The folder to list the files of
+fn list_files(root: &Path) -> Vec<String> {
+    let mut files = vec![];
+    let mut folders = vec![root.to_path_buf()];
+    while let Some(folder) = folders.pop() {
+        let entries = match fs::read_dir(folder.as_path()) {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!("Failed to read folder {:?}: {}", folder, e);
+                continue;
+            }
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                folders.push(path);
+            } else if let Ok(relative_path) = path.strip_prefix(root) {
+                files.push(relative_path.to_string_lossy().replace('\\', "/"));
+            }
         }
-        SubCommand::SetPath { path } => {
-            let app_state = state.0.lock().unwrap();
-            set_game_path(app_state, path);
-        }
-        SubCommand::Update { query } => match query.as_str() {
-            "profile" => set_profile(state),
-            _ => {
-                let filtered_mod_name = query.replace(" ", "").to_lowercase();
-                println!("Filtered mod name: {}", filtered_mod_name);
-            }
-        },
     }
+    files.sort();
+    files
 }
 
-fn print_and_log(message: String) {
-    println!("{}", message);
-    info!("{}", message);
+/// Recursively list the files under a folder with their SHA256, as paths relative to that folder
+/// # Arguments
+/// * `root` - The folder to list the files of
+fn hash_files(root: &Path) -> Vec<InstalledFile> {
+    list_files(root)
+        .into_iter()
+        .map(|path| InstalledFile {
+            sha256: digest_file(root.join(path.as_str()))
+                .unwrap_or_default()
+                .to_lowercase(),
+            path,
+        })
+        .collect()
+}
+
+/// The path to the folder containing the settings, log and cached files
+fn settings_dir() -> PathBuf {
+    let base_dir = BaseDirs::new().unwrap();
+    [base_dir.data_dir().to_str().unwrap(), SETTINGS_FOLDER]
+        .iter()
+        .collect()
+}
+
+/// The current time in seconds since the Unix epoch
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
 }
 
 /// Enables the Modding API
 /// # Arguments
+/// * `release` - The release of the Modding API to install if it is missing, or None for the
+///   latest one
 /// * `state` - The state of the application
-fn enable_api(state: &AppState) {
+fn enable_api(release: Option<&ApiRelease>, state: &AppState) -> Result<(), DownloadError> {
     let mods_path: String;
     {
         let app_state = state.0.lock().unwrap();
@@ -1230,18 +3906,31 @@ fn enable_api(state: &AppState) {
                 e
             ),
         }
-        return;
+        return Ok(());
     } else if !modded_assembly.exists() && !vanilla_assembly.exists() {
         warn!("Neither the modded or vanilla assembly backups exists, downloading API.");
-        match fs::rename(assembly.clone(), vanilla_assembly) {
+        match fs::rename(assembly.clone(), vanilla_assembly.clone()) {
             Ok(_) => info!("Successfully renamed Assembly-CSharp to modded assembly backup."),
             Err(e) => error!(
                 "Failed to rename Assembly-CSharp to modded assembly backup: {}",
                 e
             ),
         }
-        install_api(state);
-        return;
+        let result = match release {
+            Some(release) => install_api_release(release, state),
+            None => install_api(state),
+        };
+        if let Err(e) = result {
+            // Put the vanilla assembly back so the game is left untouched
+            if !assembly.exists() {
+                match fs::rename(vanilla_assembly, assembly) {
+                    Ok(_) => info!("Successfully restored vanilla Assembly-CSharp."),
+                    Err(e) => error!("Failed to restore vanilla Assembly-CSharp: {}", e),
+                }
+            }
+            return Err(e);
+        }
+        return Ok(());
     } else if modded_assembly.exists() && vanilla_assembly.exists() {
         warn!("Somehow, both assembly backups exist.");
         match fs::remove_file(modded_assembly) {
@@ -1252,78 +3941,342 @@ fn enable_api(state: &AppState) {
             Ok(_) => info!("Successfully removed vanilla backup."),
             Err(e) => error!("Failed to remove vanilla backup: {}", e),
         }
-        return;
+        return Ok(());
     }
 
     warn!("No action was taken.");
+    Ok(())
 }
 
-/// Sets the current mod profile in settings
+/// Resolve the URLs to fetch ModLinks and ApiLinks from; Command line arguments and
+/// environment variables take precedence over settings
 /// # Arguments
+/// * `args` - The arguments passed to the application
 /// * `state` - The state of the application
-fn set_profile(state: &AppState) {
-    let mods_to_install: Vec<String>;
-    let manifests: Vec<LocalModManifest>;
-    {
-        let mut app_state = state.0.lock().unwrap();
-        let mut profile_name = String::new();
-        print!("Enter profile name: ");
-        io::stdout().flush().unwrap();
-        io::stdin()
-            .read_line(&mut profile_name)
-            .expect("Failed to read line.");
-        profile_name = profile_name.trim().to_string();
-        let mut profile_exists = false;
-        for profile in app_state.settings.profiles.iter() {
-            if profile.name == profile_name {
-                profile_exists = true;
-                break;
-            }
-        }
+fn set_links_urls(args: &Arguments, state: &AppState) {
+    let mut app_state = state.0.lock().unwrap();
+    let mut mod_links_urls = vec![args
+        .modlinks_url
+        .clone()
+        .unwrap_or_else(|| app_state.settings.mod_links_url.clone())];
+    if args.modlinks_mirrors.is_empty() {
+        mod_links_urls.extend(app_state.settings.mod_links_mirrors.clone());
+    } else {
+        mod_links_urls.extend(args.modlinks_mirrors.clone());
+    }
 
-        while !profile_exists {
-            println!("Profile {:?} does not exist.", profile_name);
-            profile_name = "".to_string();
-            print!("Enter profile name: ");
-            io::stdout().flush().unwrap();
-            io::stdin()
-                .read_line(&mut profile_name)
-                .expect("Failed to read line.");
-            profile_name = profile_name.trim().to_string();
-            profile_exists = false;
-            for profile in app_state.settings.profiles.iter() {
-                if profile.name == profile_name {
-                    profile_exists = true;
-                    break;
-                }
-            }
-        }
+    let mut api_links_urls = vec![args
+        .apilinks_url
+        .clone()
+        .unwrap_or_else(|| app_state.settings.api_links_url.clone())];
+    if args.apilinks_mirrors.is_empty() {
+        api_links_urls.extend(app_state.settings.api_links_mirrors.clone());
+    } else {
+        api_links_urls.extend(args.apilinks_mirrors.clone());
+    }
+
+    app_state.mod_links_urls = mod_links_urls;
+    app_state.api_links_urls = api_links_urls;
+}
+
+/// Sets the current mod profile in settings and applies it
+/// # Arguments
+/// * `profile_name` - The name of the profile, or None to ask for it
+/// * `state` - The state of the application
+fn set_profile(profile_name: Option<String>, state: &AppState) -> Result<(), String> {
+    let profile_name = {
+        let app_state = state.0.lock().unwrap();
+        existing_profile_name(profile_name, "Enter profile name: ", &app_state)?
+    };
 
-        app_state.settings.current_profile = profile_name.clone();
+    apply_profile(profile_name.as_str(), state)?;
+    // Only switch once the profile's mods are in place, so a failed apply keeps the old profile
+    state.0.lock().unwrap().settings.current_profile = profile_name;
+    Ok(())
+}
 
-        match app_state
+/// Install the mods of a profile and their dependencies and disable every other mod; Pinned
+/// mods are installed at a version that satisfies their pin, or left out if ModLinks no longer
+/// offers one and the installed version does not satisfy it either
+/// # Arguments
+/// * `profile_name` - The name of the profile
+/// * `state` - The state of the application
+fn apply_profile(profile_name: &str, state: &AppState) -> Result<(), String> {
+    let (profile, manifests, installed_mods) = {
+        let app_state = state.0.lock().unwrap();
+        let profile = app_state
             .settings
             .profiles
             .iter()
             .find(|profile| profile.name == profile_name)
-        {
-            Some(profile) => mods_to_install = profile.mods.clone(),
+            .cloned()
+            .ok_or_else(|| format!("profile {:?} does not exist", profile_name))?;
+        (
+            profile,
+            app_state.settings.mod_links.manifests.clone(),
+            app_state.settings.installed_mods.clone(),
+        )
+    };
+
+    let pins = profile_pins(&profile);
+    let mut mods_to_install: Vec<String> = vec![];
+    let mut mods_to_reinstall: Vec<(String, String, Constraint)> = vec![];
+    for profile_mod in profile.mods.iter() {
+        let manifest = match resolver::find(profile_mod.name.as_str(), &manifests) {
+            Some(manifest) => manifest,
             None => {
-                error!("Failed to find profile {:?}.", profile_name);
-                return;
+                // Let resolving the plan report the missing mod
+                mods_to_install.push(profile_mod.name.clone());
+                continue;
+            }
+        };
+        if let Some(pin) = pin_for(&pins, manifest.name.as_str()) {
+            let installed_version = installed_mods
+                .iter()
+                .find(|installed| installed.name == manifest.name)
+                .map(|installed| installed.version.as_str());
+            let installed_matches = manifest.installed
+                && installed_version.map_or(false, |version| pin.matches(version));
+            if !pin.matches(manifest.version.as_str()) {
+                if !installed_matches {
+                    print_and_log(format!(
+                        "Warning: mod {:?} is pinned to {}, but ModLinks only offers version {}; \
+                         leaving it out.",
+                        manifest.name, pin, manifest.version
+                    ));
+                    continue;
+                }
+                print_and_log(format!(
+                    "Warning: mod {:?} is pinned to {}, but ModLinks only offers version {}; \
+                     keeping the installed version {}.",
+                    manifest.name,
+                    pin,
+                    manifest.version,
+                    installed_version.unwrap_or_default()
+                ));
+            } else if manifest.installed && !installed_matches {
+                mods_to_reinstall.push((
+                    manifest.name.clone(),
+                    manifest.version.clone(),
+                    pin.clone(),
+                ));
             }
         }
-
-        manifests = app_state.settings.mod_links.manifests.clone();
+        mods_to_install.push(manifest.name.clone());
     }
 
+    // Dependencies of the profile's mods stay enabled even if they are not listed in it
+    let mods_to_keep: Vec<String> = resolver::resolve(&mods_to_install, &manifests)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|manifest| manifest.name)
+        .collect();
+    install_mods(mods_to_install, state).map_err(|e| e.to_string())?;
+    for (mod_name, mod_version, pin) in mods_to_reinstall {
+        print_and_log(format!(
+            "Installing mod {:?} {} to satisfy its pin {}.",
+            mod_name, mod_version, pin
+        ));
+        reinstall_mod(mod_name, state).map_err(|e| e.to_string())?;
+    }
     manifests.iter().for_each(|manifest| {
-        if mods_to_install.contains(&manifest.name) && !manifest.enabled {
-            install_mod(manifest.name.clone(), state);
-        } else if !mods_to_install.contains(&manifest.name) && manifest.enabled {
+        if !mods_to_keep.contains(&manifest.name) && manifest.enabled {
             disable_mod(manifest.name.clone(), state);
         }
     });
+
+    Ok(())
+}
+
+/// Parse the pins of the mods in a profile, keyed by mod name without spaces and in lowercase;
+/// Pins that cannot be parsed are left out with a warning
+/// # Arguments
+/// * `profile` - The profile
+fn profile_pins(profile: &Profile) -> HashMap<String, Constraint> {
+    let mut pins = HashMap::new();
+    for profile_mod in profile.mods.iter() {
+        if let Some(pin) = &profile_mod.pin {
+            match Constraint::from_str(pin.as_str()) {
+                Ok(constraint) => {
+                    pins.insert(profile_mod.name.replace(' ', "").to_lowercase(), constraint);
+                }
+                Err(e) => print_and_log(format!(
+                    "Warning: ignoring the pin of mod {:?} in profile {:?}: {}",
+                    profile_mod.name, profile.name, e
+                )),
+            }
+        }
+    }
+
+    pins
+}
+
+/// Set or remove the pin of a mod in a profile, adding the mod to the profile if needed
+/// # Arguments
+/// * `query` - The name of the mod
+/// * `constraint` - Some version or constraint to pin the mod to, or the installed version if
+///   it is None; None to remove the pin
+/// * `profile_name` - The profile to change, or None for the current profile
+/// * `state` - The state of the application
+fn pin_mod(
+    query: String,
+    constraint: Option<Option<String>>,
+    profile_name: Option<String>,
+    state: &AppState,
+) -> Result<(), String> {
+    let mut app_state = state.0.lock().unwrap();
+    let profile_name = profile_name.unwrap_or_else(|| app_state.settings.current_profile.clone());
+    if profile_name.is_empty() {
+        return Err("no profile was given and no profile is selected".to_string());
+    }
+    let manifest = resolver::find(query.as_str(), &app_state.settings.mod_links.manifests)
+        .cloned()
+        .ok_or_else(|| format!("mod {:?} not found", query))?;
+
+    let pin = match constraint {
+        Some(Some(constraint)) => Some(Constraint::from_str(constraint.as_str())?.to_string()),
+        Some(None) => {
+            let installed_version = app_state
+                .settings
+                .installed_mods
+                .iter()
+                .find(|installed| installed.name == manifest.name)
+                .map(|installed| installed.version.clone())
+                .filter(|version| !version.is_empty())
+                .ok_or_else(|| {
+                    format!(
+                        "mod {:?} is not installed; give a version to pin it to",
+                        manifest.name
+                    )
+                })?;
+            Some(installed_version)
+        }
+        None => None,
+    };
+
+    let profile = app_state
+        .settings
+        .profiles
+        .iter_mut()
+        .find(|profile| profile.name == profile_name)
+        .ok_or_else(|| format!("profile {:?} does not exist", profile_name))?;
+    let normalized = manifest.name.replace(' ', "").to_lowercase();
+    match profile
+        .mods
+        .iter_mut()
+        .find(|profile_mod| profile_mod.name.replace(' ', "").to_lowercase() == normalized)
+    {
+        Some(profile_mod) => profile_mod.pin = pin.clone(),
+        None => profile.mods.push(ProfileMod {
+            name: manifest.name.clone(),
+            pin: pin.clone(),
+        }),
+    }
+
+    match pin {
+        Some(pin) => {
+            print_and_log(format!(
+                "Pinned mod {:?} to {} in profile {:?}.",
+                manifest.name, pin, profile_name
+            ));
+            let constraint = Constraint::from_str(pin.as_str())?;
+            if !constraint.matches(manifest.version.as_str()) {
+                print_and_log(format!(
+                    "Warning: ModLinks only offers version {} of mod {:?}, which does not \
+                     satisfy the pin.",
+                    manifest.version, manifest.name
+                ));
+            }
+        }
+        None => print_and_log(format!(
+            "Removed the pin of mod {:?} in profile {:?}.",
+            manifest.name, profile_name
+        )),
+    }
+
+    Ok(())
+}
+
+/// Get the pin of a mod from a set of pins
+/// # Arguments
+/// * `pins` - The pins, as returned by `profile_pins`
+/// * `mod_name` - The name of the mod
+fn pin_for<'a>(pins: &'a HashMap<String, Constraint>, mod_name: &str) -> Option<&'a Constraint> {
+    pins.get(mod_name.replace(' ', "").to_lowercase().as_str())
+}
+
+/// Get the pins of the current profile, if there is one
+/// # Arguments
+/// * `state` - The state of the application
+fn current_pins(state: &AppState) -> HashMap<String, Constraint> {
+    let app_state = state.0.lock().unwrap();
+    app_state
+        .settings
+        .profiles
+        .iter()
+        .find(|profile| profile.name == app_state.settings.current_profile)
+        .map(profile_pins)
+        .unwrap_or_default()
+}
+
+/// Uninstall a mod after checking whether other installed mods depend on it, asking for
+/// confirmation if they do; Returns whether the mod was uninstalled, which it is not if it was
+/// not installed to begin with
+/// # Arguments
+/// * `query` - The name of the mod, without spaces and in lowercase
+/// * `cascade` - Whether to also uninstall the mods that depend on it instead of asking
+/// * `state` - The state of the application
+fn remove_mod(query: String, cascade: bool, state: &AppState) -> bool {
+    let manifests: Vec<LocalModManifest> = {
+        let app_state = state.0.lock().unwrap();
+        app_state.settings.mod_links.manifests.clone()
+    };
+    // Mods that are not in the mod list may still have been installed by hand
+    let mod_name = resolver::find(query.as_str(), &manifests)
+        .map_or(query.clone(), |manifest| manifest.name.clone());
+    let installed = {
+        let app_state = state.0.lock().unwrap();
+        let mods_path = app_state.settings.mods_path.as_str();
+        let mod_path: PathBuf = [mods_path, mod_name.as_str()].iter().collect();
+        let disabled_mod_path: PathBuf =
+            [mods_path, "Disabled", mod_name.as_str()].iter().collect();
+        mod_path.exists() || disabled_mod_path.exists()
+    };
+    if !installed {
+        print_and_log(format!("Mod {:?} is not installed.", mod_name));
+        return false;
+    }
+
+    let dependents = resolver::dependents(mod_name.as_str(), &manifests);
+    if !dependents.is_empty() {
+        println!("The following installed mods depend on {:?}:", mod_name);
+        dependents
+            .iter()
+            .for_each(|dependent| println!("\t- {}", dependent));
+        if cascade {
+            for dependent in dependents {
+                uninstall_mod(dependent.replace(' ', "").to_lowercase(), state);
+                print_and_log(format!("Uninstalled mod {:?}.", dependent));
+            }
+        } else if !confirm(
+            format!(
+                "Uninstall {:?} anyway? The mods above may stop working.",
+                mod_name
+            )
+            .as_str(),
+            state.0.lock().unwrap().non_interactive,
+        ) {
+            print_and_log(format!(
+                "Mod {:?} was not uninstalled; use --cascade to also uninstall its dependents.",
+                mod_name
+            ));
+            return false;
+        }
+    }
+
+    uninstall_mod(mod_name.replace(' ', "").to_lowercase(), state);
+    print_and_log(format!("Uninstalled mod {:?}.", mod_name));
+    true
 }
 
 /// Removes a mod folder from disk
@@ -1386,11 +4339,520 @@ fn uninstall_mod(mut mod_name: String, state: &AppState) {
             manifests = app_state.settings.mod_links.manifests.clone();
         }
         let mut app_state = state.0.lock().unwrap();
-        for i in 0..manifests.len() {
-            if manifests[i].name == mod_name {
+        for (i, manifest) in manifests.iter().enumerate() {
+            if manifest.name == mod_name {
                 app_state.settings.mod_links.manifests[i].installed = false;
                 app_state.settings.mod_links.manifests[i].enabled = false;
             }
         }
+        app_state
+            .settings
+            .installed_mods
+            .retain(|installed| installed.name != mod_name);
+    }
+}
+
+/// Uninstall the mods that were only installed as dependencies and that no explicitly installed
+/// mod needs anymore
+/// # Arguments
+/// * `dry_run` - Whether to only list the mods instead of uninstalling them
+/// * `state` - The state of the application
+fn autoremove(dry_run: bool, state: &AppState) {
+    let orphans: Vec<String> = {
+        let app_state = state.0.lock().unwrap();
+        let manifests = &app_state.settings.mod_links.manifests;
+        let installed_mods = &app_state.settings.installed_mods;
+        let is_dependency = |name: &String| {
+            installed_mods
+                .iter()
+                .any(|installed| &installed.name == name && !installed.explicit)
+        };
+        // Mods installed without a record, e.g. by hand, are treated as explicit
+        let explicit: Vec<String> = manifests
+            .iter()
+            .filter(|manifest| manifest.installed && !is_dependency(&manifest.name))
+            .map(|manifest| manifest.name.clone())
+            .collect();
+        resolver::unneeded(&explicit, manifests)
+            .into_iter()
+            .filter(is_dependency)
+            .collect()
+    };
+
+    if orphans.is_empty() {
+        println!("No unneeded dependencies are installed.");
+        return;
+    }
+
+    if dry_run {
+        println!("The following mods would be uninstalled:");
+        orphans.iter().for_each(|name| println!("\t- {}", name));
+        return;
+    }
+
+    for name in orphans {
+        uninstall_mod(name.replace(' ', "").to_lowercase(), state);
+        print_and_log(format!("Uninstalled unneeded dependency {:?}.", name));
+    }
+}
+
+/// Reinstall a mod, keeping it disabled if it was disabled and keeping the installed version if
+/// the reinstall fails
+/// # Arguments
+/// * `mod_name` - The name of the mod folder
+/// * `state` - The state of the application
+fn reinstall_mod(mod_name: String, state: &AppState) -> Result<(), DownloadError> {
+    let enabled = {
+        let app_state = state.0.lock().unwrap();
+        app_state
+            .settings
+            .mod_links
+            .manifests
+            .iter()
+            .any(|manifest| manifest.name == mod_name && manifest.enabled)
+    };
+    let explicit = {
+        let app_state = state.0.lock().unwrap();
+        app_state
+            .settings
+            .installed_mods
+            .iter()
+            .find(|installed| installed.name == mod_name)
+            .map_or(true, |installed| installed.explicit)
+    };
+    // A new version may have added dependencies that are not installed yet
+    let mut plan = {
+        let app_state = state.0.lock().unwrap();
+        resolver::resolve(
+            std::slice::from_ref(&mod_name),
+            &app_state.settings.mod_links.manifests,
+        )
+        .map_err(DownloadError::Resolve)?
+    };
+    // The installed version is only replaced once the new one is in place, and is put back if
+    // installing it fails
+    plan.iter_mut()
+        .filter(|manifest| manifest.name == mod_name)
+        .for_each(|manifest| {
+            manifest.installed = false;
+            manifest.enabled = false;
+        });
+    install_plan(plan, &[], state)?;
+    {
+        let mut app_state = state.0.lock().unwrap();
+        app_state
+            .settings
+            .installed_mods
+            .iter_mut()
+            .filter(|installed| installed.name == mod_name)
+            .for_each(|installed| installed.explicit = explicit);
+    }
+    if !enabled {
+        disable_mod(mod_name, state);
+    }
+
+    Ok(())
+}
+
+/// Update every installed mod whose version is behind ModLinks
+/// # Arguments
+/// * `state` - The state of the application
+fn update_all_mods(state: &AppState) -> Result<(), DownloadError> {
+    let installed_mods: Vec<String> = {
+        let app_state = state.0.lock().unwrap();
+        app_state
+            .settings
+            .mod_links
+            .manifests
+            .iter()
+            .filter(|manifest| manifest.installed)
+            .map(|manifest| manifest.name.clone())
+            .collect()
+    };
+    for mod_name in installed_mods {
+        update_mod(mod_name, state)?;
+    }
+
+    Ok(())
+}
+
+/// Update the Modding API if the installed version differs from the one in ApiLinks
+/// # Arguments
+/// * `state` - The state of the application
+fn update_api(state: &AppState) -> Result<(), DownloadError> {
+    let (mods_path, installed_version, api_links_urls, offline) = {
+        let app_state = state.0.lock().unwrap();
+        (
+            app_state.settings.mods_path.clone(),
+            app_state.settings.api_version.clone(),
+            app_state.api_links_urls.clone(),
+            app_state.offline,
+        )
+    };
+    let vanilla_assembly: PathBuf = [mods_path.as_str(), "..", "Assembly-CSharp.dll.vanilla"]
+        .iter()
+        .collect();
+    if !vanilla_assembly.exists() {
+        print_and_log("The Modding API is not installed and enabled.".to_string());
+        return Ok(());
+    }
+
+    let api_links = match fetch_api_links(&api_links_urls, offline) {
+        Some(api_links) => api_links,
+        None => {
+            return Err(DownloadError::Network(
+                "failed to fetch API links".to_string(),
+            ))
+        }
+    };
+    let available_version = api_links.manifest.version;
+    if !version::is_outdated(installed_version.as_str(), available_version.as_str()) {
+        print_and_log(format!(
+            "The Modding API is up to date ({}).",
+            installed_version
+        ));
+        return Ok(());
+    }
+
+    print_and_log(format!(
+        "Updating the Modding API from {} to {}.",
+        if installed_version.is_empty() {
+            "an unknown version"
+        } else {
+            installed_version.as_str()
+        },
+        available_version
+    ));
+    install_api(state)
+}
+
+/// Update a mod and any of its dependencies whose installed versions are behind ModLinks
+/// # Arguments
+/// * `mod_name` - The name of the mod to update
+/// * `state` - The state of the application
+fn update_mod(mod_name: String, state: &AppState) -> Result<(), DownloadError> {
+    let (manifests, installed_mods) = {
+        let app_state = state.0.lock().unwrap();
+        (
+            app_state.settings.mod_links.manifests.clone(),
+            app_state.settings.installed_mods.clone(),
+        )
+    };
+    let manifest = match manifests.iter().find(|manifest| {
+        manifest.name.replace(' ', "").to_lowercase() == mod_name.replace(' ', "").to_lowercase()
+    }) {
+        Some(manifest) => manifest,
+        None => {
+            print_and_log(format!("Mod {:?} not found.", mod_name));
+            return Ok(());
+        }
+    };
+    if !manifest.installed {
+        print_and_log(format!("Mod {:?} is not installed.", manifest.name));
+        return Ok(());
+    }
+
+    // Check the mod and all of its dependencies, dependencies first
+    let to_check = resolver::resolve(std::slice::from_ref(&manifest.name), &manifests)
+        .map_err(DownloadError::Resolve)?;
+
+    let pins = current_pins(state);
+    let mut updated = false;
+    for current in to_check {
+        if !current.installed {
+            continue;
+        }
+        let installed_version = installed_mods
+            .iter()
+            .find(|installed| installed.name == current.name)
+            .map(|installed| installed.version.as_str())
+            .unwrap_or("");
+        if let Some(pin) = pin_for(&pins, current.name.as_str()) {
+            if !pin.matches(current.version.as_str()) {
+                if installed_version != current.version {
+                    print_and_log(format!(
+                        "Warning: not updating mod {:?}; it is pinned to {}, but ModLinks only \
+                         offers version {}.",
+                        current.name, pin, current.version
+                    ));
+                }
+                continue;
+            }
+        }
+        if version::is_outdated(installed_version, current.version.as_str()) {
+            print_and_log(format!(
+                "Updating mod {:?} from {} to {}.",
+                current.name,
+                if installed_version.is_empty() {
+                    "an unknown version"
+                } else {
+                    installed_version
+                },
+                current.version
+            ));
+            reinstall_mod(current.name.clone(), state)?;
+            updated = true;
+        }
+    }
+
+    if !updated {
+        print_and_log(format!("Mod {:?} is up to date.", manifest.name));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TestDir;
+
+    #[test]
+    fn tells_artifact_kinds_apart() {
+        let dir = TestDir::new("artifact-kind");
+        let cases: [(&str, &[u8], Option<bool>); 5] = [
+            ("archive", b"PK\x03\x04rest", Some(true)),
+            ("empty-archive", b"PK\x05\x06rest", Some(true)),
+            ("dll", b"MZ\x90\x00", Some(false)),
+            ("short", b"M", None),
+            ("html", b"<!DOCTYPE html>", None),
+        ];
+        for (name, bytes, is_zip) in cases {
+            let path = dir.0.join(name);
+            fs::write(path.as_path(), bytes).unwrap();
+            let kind = artifact_kind(path.as_path()).ok();
+            assert_eq!(
+                kind.map(|kind| matches!(kind, ArtifactKind::Zip)),
+                is_zip,
+                "{}",
+                name
+            );
+        }
+        assert!(artifact_kind(dir.0.join("missing").as_path()).is_err());
+    }
+
+    #[test]
+    fn names_dlls_after_their_url() {
+        assert_eq!(
+            dll_file_name("Mod", "https://example.com/releases/MyMod.dll"),
+            "MyMod.dll"
+        );
+        assert_eq!(
+            dll_file_name("Mod", "https://example.com/MyMod.DLL?raw=true#top"),
+            "MyMod.DLL"
+        );
+        assert_eq!(
+            dll_file_name("Mod", "https://example.com/download?id=3"),
+            "Mod.dll"
+        );
+        assert_eq!(dll_file_name("Mod", ""), "Mod.dll");
+    }
+
+    #[test]
+    fn reads_content_range_starts() {
+        let headers = |value: &str| {
+            let mut headers = reqwest::header::HeaderMap::new();
+            headers.insert(
+                reqwest::header::CONTENT_RANGE,
+                reqwest::header::HeaderValue::from_str(value).unwrap(),
+            );
+            headers
+        };
+        assert_eq!(range_start(&headers("bytes 100-999/1000")), Some(100));
+        assert_eq!(range_start(&headers("bytes 0-99/*")), Some(0));
+        assert_eq!(range_start(&headers("bytes */1000")), None);
+        assert_eq!(range_start(&headers("items 100-999/1000")), None);
+        assert_eq!(range_start(&reqwest::header::HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn keeps_validators_next_to_downloads() {
+        assert_eq!(
+            validator_path(Path::new("Downloads/abc/Mod")),
+            PathBuf::from("Downloads/abc/Mod.validator")
+        );
+        assert_eq!(
+            validator_path(Path::new("Downloads/abc/ModdingApi.zip")),
+            PathBuf::from("Downloads/abc/ModdingApi.zip.validator")
+        );
+    }
+
+    #[test]
+    fn finds_local_paths_in_urls() {
+        assert_eq!(
+            local_path("file:///tmp/ModLinks.xml"),
+            Some(PathBuf::from("/tmp/ModLinks.xml"))
+        );
+        assert_eq!(
+            local_path("file:///C:/Mirror/ModLinks.xml"),
+            Some(PathBuf::from("C:/Mirror/ModLinks.xml"))
+        );
+        assert_eq!(
+            local_path("Mirror/ModLinks.xml"),
+            Some(PathBuf::from("Mirror/ModLinks.xml"))
+        );
+        assert_eq!(local_path("https://example.com/ModLinks.xml"), None);
+    }
+
+    #[test]
+    fn formats_timestamps_in_utc() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00 UTC");
+        assert_eq!(format_timestamp(1664978580), "2022-10-05 14:03 UTC");
+        // Leap day and the last minute of a year
+        assert_eq!(format_timestamp(951782400), "2000-02-29 00:00 UTC");
+        assert_eq!(format_timestamp(1704067199), "2023-12-31 23:59 UTC");
+    }
+
+    #[test]
+    fn compares_installed_files() {
+        let dir = TestDir::new("compare");
+        let root = dir.0.as_path();
+        fs::create_dir_all(root.join("Sub")).unwrap();
+        fs::write(root.join("Same.dll"), "same").unwrap();
+        fs::write(root.join("Sub/Changed.dll"), "changed").unwrap();
+        fs::write(root.join("Old.dll"), "old").unwrap();
+        fs::write(root.join("Extra.txt"), "extra").unwrap();
+        let file = |path: &str, contents: &str| InstalledFile {
+            path: path.to_string(),
+            sha256: sha256::digest(contents).to_uppercase(),
+        };
+        let files = vec![
+            file("Same.dll", "same"),
+            file("Sub/Changed.dll", "original"),
+            file("Missing.dll", "missing"),
+            // Recorded before hashes were kept
+            InstalledFile {
+                path: "Old.dll".to_string(),
+                sha256: String::new(),
+            },
+        ];
+
+        let (modified, missing, extra) = compare_files(root, &files, true);
+        assert_eq!(modified, vec!["Sub/Changed.dll"]);
+        assert_eq!(missing, vec!["Missing.dll"]);
+        assert_eq!(extra, vec!["Extra.txt"]);
+
+        let (_, _, extra) = compare_files(root, &files, false);
+        assert!(extra.is_empty());
+    }
+
+    /// Parse mod links listing one mod per (name, version)
+    fn mod_links(mods: &[(&str, &str)]) -> RemoteModLinks {
+        let manifests: String = mods
+            .iter()
+            .map(|(name, version)| {
+                format!(
+                    "<Manifest><Name>{}</Name><Version>{}</Version>\
+                     <Link SHA256=\"00\">https://example.com/{}.zip</Link>\
+                     <Dependencies /><Repository>https://example.com</Repository></Manifest>",
+                    name, version, name
+                )
+            })
+            .collect();
+        quick_xml::de::from_str(format!("<ModLinks>{}</ModLinks>", manifests).as_str()).unwrap()
+    }
+
+    #[test]
+    fn merges_sources_in_order_of_precedence() {
+        let mut merged = RemoteModLinks { manifests: vec![] };
+        add_source_mods(
+            &mut merged,
+            "studio",
+            mod_links(&[("Shared", "2.0"), ("Private", "1.0")]),
+        );
+        add_source_mods(
+            &mut merged,
+            OFFICIAL_SOURCE,
+            mod_links(&[("Public", "1.0"), ("Shared", "1.0")]),
+        );
+
+        let mods: Vec<(&str, &str, &str)> = merged
+            .manifests
+            .iter()
+            .map(|manifest| {
+                (
+                    manifest.name.as_str(),
+                    manifest.version.as_str(),
+                    manifest.source.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            mods,
+            vec![
+                ("Shared", "2.0", "studio"),
+                ("Private", "1.0", "studio"),
+                ("Public", "1.0", OFFICIAL_SOURCE),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_separate_caches_for_each_source() {
+        let urls = |url: &str| vec![url.to_string()];
+        let names = [
+            source_cache_name(OFFICIAL_SOURCE, &urls("https://example.com/ModLinks.xml")),
+            source_cache_name("a-b", &urls("https://example.com/a-b.xml")),
+            source_cache_name("a_b", &urls("https://example.com/a_b.xml")),
+            source_cache_name("a_b", &urls("https://example.com/other.xml")),
+            source_cache_name("ModLinks", &urls("https://example.com/ModLinks.xml")),
+        ];
+        assert_eq!(names[0], "ModLinks.xml");
+        for (i, name) in names.iter().enumerate() {
+            assert!(names[..i]
+                .iter()
+                .all(|other| !other.eq_ignore_ascii_case(name)));
+        }
+        assert_eq!(
+            names[1],
+            source_cache_name("a-b", &urls("https://example.com/a-b.xml"))
+        );
+    }
+
+    #[test]
+    fn puts_api_files_back_when_one_cannot_be_moved() {
+        let dir = TestDir::new("api-files");
+        let staging = dir.0.join("Staging");
+        let managed = dir.0.join("Managed");
+        let backup = managed.join("Mods").join(BACKUP_FOLDER).join("ModdingApi");
+        let stage = || {
+            fs::create_dir_all(staging.join("Sub")).unwrap();
+            fs::write(staging.join("Assembly-CSharp.dll"), "modded").unwrap();
+            fs::write(staging.join("Other.dll"), "other").unwrap();
+            fs::write(staging.join("Sub/Extra.dll"), "extra").unwrap();
+        };
+        stage();
+        fs::create_dir_all(managed.as_path()).unwrap();
+        fs::write(managed.join("Assembly-CSharp.dll"), "vanilla").unwrap();
+        // A file where a folder of the release should go
+        fs::write(managed.join("Sub"), "in the way").unwrap();
+        let files: Vec<String> = ["Assembly-CSharp.dll", "Other.dll", "Sub/Extra.dll"]
+            .iter()
+            .map(|file| file.to_string())
+            .collect();
+
+        let result = replace_api_files(&files, &staging, &managed, &backup);
+        assert!(matches!(result, Err(DownloadError::Io(_))));
+        assert_eq!(
+            fs::read_to_string(managed.join("Assembly-CSharp.dll")).unwrap(),
+            "vanilla"
+        );
+        assert!(!managed.join("Assembly-CSharp.dll.vanilla").exists());
+        assert!(!managed.join("Other.dll").exists());
+        assert!(!backup.exists());
+
+        fs::remove_file(managed.join("Sub")).unwrap();
+        stage();
+        replace_api_files(&files, &staging, &managed, &backup).unwrap();
+        assert_eq!(
+            fs::read_to_string(managed.join("Assembly-CSharp.dll")).unwrap(),
+            "modded"
+        );
+        assert_eq!(
+            fs::read_to_string(managed.join("Assembly-CSharp.dll.vanilla")).unwrap(),
+            "vanilla"
+        );
+        assert!(managed.join("Sub/Extra.dll").is_file());
+        assert!(!backup.exists());
     }
 }
@@ -1,3 +1,4 @@
+use crate::detect::Platform;
 use serde::{self, Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, PartialEq, Serialize)]
@@ -18,6 +19,19 @@ pub struct ApiPlatformLinks {
     pub windows: ApiLink,
 }
 
+impl ApiPlatformLinks {
+    /// Get the link to the Modding API for a build of the game
+    /// # Arguments
+    /// * `platform` - The platform the game is built for
+    pub fn get(&self, platform: Platform) -> &ApiLink {
+        match platform {
+            Platform::Linux => &self.linux,
+            Platform::Mac => &self.mac,
+            Platform::Windows => &self.windows,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, PartialEq, Serialize)]
 pub struct ApiFiles {
     #[serde(rename = "File")]
@@ -40,30 +54,12 @@ pub struct ApiLinks {
     pub manifest: ApiManifest
 }
 
-impl ApiLinks {
-    /// Create a new instance of an api links object
-    pub fn new() -> ApiLinks {
-        ApiLinks {
-            manifest: ApiManifest {
-                version: "".to_string(),
-                links: ApiPlatformLinks {
-                    linux: ApiLink {
-                        sha256: "".to_string(),
-                        link: "".to_string(),
-                    },
-                    mac: ApiLink {
-                        sha256: "".to_string(),
-                        link: "".to_string(),
-                    },
-                    windows: ApiLink {
-                        sha256: "".to_string(),
-                        link: "".to_string(),
-                    }
-                },
-                files: ApiFiles {
-                    files: vec![],
-                }
-            },
-        }
-    }
-}
\ No newline at end of file
+/// An exact release of the Modding API for one platform
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApiRelease {
+    pub version: String,
+    pub url: String,
+    pub sha256: String,
+    /// The files the API replaces in the Managed folder
+    pub files: Vec<String>,
+}
@@ -46,20 +46,15 @@ pub struct LocalModManifest {
     pub enabled: bool,
     #[serde(rename = "Installed")]
     pub installed: bool,
+    /// The name of the mod source that this manifest was fetched from
+    #[serde(rename = "Source", default)]
+    pub source: String,
 }
 
 /// The main mod links object loaded from settings file;
 /// local to settings file
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct LocalModLinks {
     #[serde(rename = "Manifest", default)]
     pub manifests: Vec<LocalModManifest>,
 }
-
-impl Default for LocalModLinks {
-    fn default() -> Self {
-        LocalModLinks {
-            manifests: vec![],
-        }
-    }
-}
\ No newline at end of file
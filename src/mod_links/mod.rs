@@ -1,3 +1,5 @@
 pub mod api;
 pub mod local;
-pub mod remote;
\ No newline at end of file
+pub mod remote;
+pub mod resolver;
+pub mod version;
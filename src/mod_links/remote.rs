@@ -24,6 +24,9 @@ pub struct RemoteModManifest {
     pub enabled: bool,
     #[serde(skip_deserializing, rename = "Installed")]
     pub installed: bool,
+    /// The name of the mod source that this manifest was fetched from
+    #[serde(skip_deserializing, rename = "Source")]
+    pub source: String,
 }
 
 /// The main mod links object fetched from GitHub
@@ -32,12 +35,3 @@ pub struct RemoteModLinks {
     #[serde(rename = "Manifest", default)]
     pub manifests: Vec<RemoteModManifest>,
 }
-
-impl RemoteModLinks {
-    /// Create a new instance of a remote mod links object
-    pub fn new() -> RemoteModLinks {
-        RemoteModLinks {
-            manifests: vec![],
-        }
-    }
-}
\ No newline at end of file
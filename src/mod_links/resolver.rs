@@ -0,0 +1,338 @@
+use crate::mod_links::local::LocalModManifest;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Problems found while resolving the dependencies of a set of mods
+#[derive(Debug)]
+pub enum ResolveError {
+    /// A requested mod is not in the mod list
+    NotFound(String),
+    /// Mods depend on mods that are not in the mod list, as (mod, dependency) pairs
+    MissingDependencies(Vec<(String, String)>),
+    /// Mods depend on each other in a loop; the first mod is repeated at the end
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolveError::NotFound(name) => write!(f, "mod {:?} not found", name),
+            ResolveError::MissingDependencies(missing) => write!(
+                f,
+                "missing dependencies: {}",
+                missing
+                    .iter()
+                    .map(|(name, dependency)| format!("{:?} needs {:?}", name, dependency))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            ResolveError::Cycle(cycle) => {
+                write!(f, "dependency cycle: {}", cycle.join(" -> "))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mark {
+    Visiting,
+    Done,
+}
+
+/// Normalize a mod name for lookups, ignoring spaces and case
+fn normalize(name: &str) -> String {
+    name.replace(' ', "").to_lowercase()
+}
+
+/// Find a manifest by name, preferring an exact match over a normalized one
+/// # Arguments
+/// * `name` - The name of the mod
+/// * `manifests` - The list of mods to search
+pub fn find<'a>(name: &str, manifests: &'a [LocalModManifest]) -> Option<&'a LocalModManifest> {
+    manifests
+        .iter()
+        .find(|manifest| manifest.name == name)
+        .or_else(|| {
+            let name = normalize(name);
+            manifests
+                .iter()
+                .find(|manifest| normalize(manifest.name.as_str()) == name)
+        })
+}
+
+/// Build the install plan for a set of mods: every mod and all of its dependencies, each listed
+/// once and after all of the mods it depends on; Ties are broken by name so the plan is the
+/// same on every run
+/// # Arguments
+/// * `names` - The names of the mods to install
+/// * `manifests` - The list of all known mods
+pub fn resolve(
+    names: &[String],
+    manifests: &[LocalModManifest],
+) -> Result<Vec<LocalModManifest>, ResolveError> {
+    let mut roots = vec![];
+    for name in names {
+        match find(name.as_str(), manifests) {
+            Some(manifest) => roots.push(manifest),
+            None => return Err(ResolveError::NotFound(name.clone())),
+        }
+    }
+    roots.sort_by(|a, b| a.name.cmp(&b.name));
+    roots.dedup_by(|a, b| a.name == b.name);
+
+    // Check the whole graph for missing dependencies before ordering anything
+    let mut missing = vec![];
+    let mut seen: Vec<&str> = vec![];
+    let mut pending = roots.clone();
+    while let Some(manifest) = pending.pop() {
+        if seen.contains(&manifest.name.as_str()) {
+            continue;
+        }
+        seen.push(manifest.name.as_str());
+        for dependency in manifest.dependencies.dependencies.iter() {
+            match find(dependency.as_str(), manifests) {
+                Some(dependency) => pending.push(dependency),
+                None => missing.push((manifest.name.clone(), dependency.clone())),
+            }
+        }
+    }
+    if !missing.is_empty() {
+        missing.sort();
+        missing.dedup();
+        return Err(ResolveError::MissingDependencies(missing));
+    }
+
+    let mut marks: HashMap<String, Mark> = HashMap::new();
+    let mut plan = vec![];
+    for root in roots {
+        let mut path = vec![];
+        visit(root, manifests, &mut marks, &mut path, &mut plan)?;
+    }
+
+    Ok(plan)
+}
+
+/// Depth-first visit of a mod's dependencies, appending each mod to the plan after its
+/// dependencies
+fn visit(
+    manifest: &LocalModManifest,
+    manifests: &[LocalModManifest],
+    marks: &mut HashMap<String, Mark>,
+    path: &mut Vec<String>,
+    plan: &mut Vec<LocalModManifest>,
+) -> Result<(), ResolveError> {
+    match marks.get(&manifest.name) {
+        Some(Mark::Done) => return Ok(()),
+        Some(Mark::Visiting) => {
+            let start = path
+                .iter()
+                .position(|name| name == &manifest.name)
+                .unwrap_or(0);
+            let mut cycle = path[start..].to_vec();
+            cycle.push(manifest.name.clone());
+            return Err(ResolveError::Cycle(cycle));
+        }
+        None => {}
+    }
+
+    marks.insert(manifest.name.clone(), Mark::Visiting);
+    path.push(manifest.name.clone());
+    let mut dependencies: Vec<&LocalModManifest> = manifest
+        .dependencies
+        .dependencies
+        .iter()
+        .filter_map(|dependency| find(dependency.as_str(), manifests))
+        .collect();
+    dependencies.sort_by(|a, b| a.name.cmp(&b.name));
+    for dependency in dependencies {
+        visit(dependency, manifests, marks, path, plan)?;
+    }
+    path.pop();
+    marks.insert(manifest.name.clone(), Mark::Done);
+    plan.push(manifest.clone());
+
+    Ok(())
+}
+
+/// Find the installed mods that depend on a mod, either directly or through other mods,
+/// sorted by name
+/// # Arguments
+/// * `name` - The name of the mod
+/// * `manifests` - The list of all known mods
+pub fn dependents(name: &str, manifests: &[LocalModManifest]) -> Vec<String> {
+    let mut dependents: Vec<String> = vec![];
+    let mut pending = vec![name.to_string()];
+    while let Some(current) = pending.pop() {
+        for manifest in manifests.iter().filter(|manifest| manifest.installed) {
+            if manifest.name != name
+                && !dependents.contains(&manifest.name)
+                && manifest
+                    .dependencies
+                    .dependencies
+                    .iter()
+                    .any(|dependency| normalize(dependency) == normalize(current.as_str()))
+            {
+                dependents.push(manifest.name.clone());
+                pending.push(manifest.name.clone());
+            }
+        }
+    }
+    dependents.sort();
+    dependents
+}
+
+/// Find the installed mods that none of the given mods need, either directly or through other
+/// mods, sorted by name
+/// # Arguments
+/// * `names` - The names of the mods to keep
+/// * `manifests` - The list of all known mods
+pub fn unneeded(names: &[String], manifests: &[LocalModManifest]) -> Vec<String> {
+    let mut needed: Vec<&str> = vec![];
+    let mut pending: Vec<&LocalModManifest> = names
+        .iter()
+        .filter_map(|name| find(name.as_str(), manifests))
+        .collect();
+    while let Some(manifest) = pending.pop() {
+        if needed.contains(&manifest.name.as_str()) {
+            continue;
+        }
+        needed.push(manifest.name.as_str());
+        pending.extend(
+            manifest
+                .dependencies
+                .dependencies
+                .iter()
+                .filter_map(|dependency| find(dependency.as_str(), manifests)),
+        );
+    }
+
+    let mut unneeded: Vec<String> = manifests
+        .iter()
+        .filter(|manifest| manifest.installed && !needed.contains(&manifest.name.as_str()))
+        .map(|manifest| manifest.name.clone())
+        .collect();
+    unneeded.sort();
+    unneeded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mod_links::local::{ModDependencies, ModLink};
+
+    /// A manifest for a mod with the given dependencies
+    fn manifest(name: &str, dependencies: &[&str], installed: bool) -> LocalModManifest {
+        LocalModManifest {
+            name: name.to_string(),
+            description: String::new(),
+            version: "1.0.0.0".to_string(),
+            link: ModLink {
+                sha256: String::new(),
+                link: String::new(),
+            },
+            dependencies: ModDependencies {
+                dependencies: dependencies.iter().map(|name| name.to_string()).collect(),
+            },
+            repository: String::new(),
+            tags: None,
+            enabled: installed,
+            installed,
+            source: String::new(),
+        }
+    }
+
+    fn names(plan: &[LocalModManifest]) -> Vec<&str> {
+        plan.iter().map(|manifest| manifest.name.as_str()).collect()
+    }
+
+    fn strings(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn orders_diamond_dependencies_once() {
+        let manifests = vec![
+            manifest("Top", &["Right", "Left"], false),
+            manifest("Left", &["Base"], false),
+            manifest("Right", &["base"], false),
+            manifest("Base", &[], false),
+        ];
+
+        let plan = resolve(&strings(&["top"]), &manifests).unwrap();
+        assert_eq!(names(&plan), ["Base", "Left", "Right", "Top"]);
+        // The same plan comes out however the mods are listed
+        let reversed: Vec<LocalModManifest> = manifests.into_iter().rev().collect();
+        let plan = resolve(&strings(&["Top", "Left"]), &reversed).unwrap();
+        assert_eq!(names(&plan), ["Base", "Left", "Right", "Top"]);
+    }
+
+    #[test]
+    fn reports_every_missing_dependency() {
+        let manifests = vec![
+            manifest("A", &["B", "Gone"], false),
+            manifest("B", &["Lost"], false),
+        ];
+
+        match resolve(&strings(&["A"]), &manifests) {
+            Err(ResolveError::MissingDependencies(missing)) => assert_eq!(
+                missing,
+                [
+                    ("A".to_string(), "Gone".to_string()),
+                    ("B".to_string(), "Lost".to_string()),
+                ]
+            ),
+            result => panic!("expected missing dependencies, got {:?}", result),
+        }
+        assert!(matches!(
+            resolve(&strings(&["C"]), &manifests),
+            Err(ResolveError::NotFound(name)) if name == "C"
+        ));
+    }
+
+    #[test]
+    fn reports_cycles() {
+        let manifests = vec![
+            manifest("A", &["B"], false),
+            manifest("B", &["C"], false),
+            manifest("C", &["A"], false),
+        ];
+
+        match resolve(&strings(&["A"]), &manifests) {
+            Err(ResolveError::Cycle(cycle)) => assert_eq!(cycle, ["A", "B", "C", "A"]),
+            result => panic!("expected a cycle, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn finds_transitive_dependents() {
+        let manifests = vec![
+            manifest("Base", &[], true),
+            manifest("Middle", &["Base"], true),
+            manifest("Top", &["Middle"], true),
+            manifest("Other", &["Base"], false),
+            manifest("Unrelated", &[], true),
+        ];
+
+        // Mods that are not installed do not count
+        assert_eq!(dependents("Base", &manifests), ["Middle", "Top"]);
+        assert_eq!(dependents("Middle", &manifests), ["Top"]);
+        assert!(dependents("Top", &manifests).is_empty());
+    }
+
+    #[test]
+    fn finds_unneeded_mods() {
+        let manifests = vec![
+            manifest("Base", &[], true),
+            manifest("Middle", &["Base"], true),
+            manifest("Top", &["Middle"], true),
+            manifest("Orphan", &[], true),
+            manifest("Available", &[], false),
+        ];
+
+        assert_eq!(unneeded(&strings(&["Top"]), &manifests), ["Orphan"]);
+        assert_eq!(
+            unneeded(&strings(&["Middle"]), &manifests),
+            ["Orphan", "Top"]
+        );
+    }
+}
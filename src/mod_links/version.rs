@@ -0,0 +1,224 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// Compare two dotted version strings such as `1.5.78.11833` or `1.5.78.11833-74` component by
+/// component; missing components count as zero and non-numeric components are compared as text
+/// # Arguments
+/// * `a` - The first version
+/// * `b` - The second version
+pub fn compare(a: &str, b: &str) -> Ordering {
+    let a_parts: Vec<&str> = a.trim().split(['.', '-']).collect();
+    let b_parts: Vec<&str> = b.trim().split(['.', '-']).collect();
+    for i in 0..a_parts.len().max(b_parts.len()) {
+        let a_part = a_parts.get(i).copied().unwrap_or("0");
+        let b_part = b_parts.get(i).copied().unwrap_or("0");
+        let ordering = match (a_part.parse::<u64>(), b_part.parse::<u64>()) {
+            (Ok(a_number), Ok(b_number)) => a_number.cmp(&b_number),
+            _ => a_part.cmp(b_part),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    Ordering::Equal
+}
+
+/// Whether an installed version should be replaced by the available one; an unknown
+/// installed version is always considered out of date
+/// # Arguments
+/// * `installed` - The version that is installed
+/// * `available` - The version that is listed in ModLinks
+pub fn is_outdated(installed: &str, available: &str) -> bool {
+    installed.is_empty() || compare(installed, available) == Ordering::Less
+}
+
+/// How a version is compared with the version in a requirement
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Comparison {
+    Exact,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    /// At least the version, with the same components except for the last one
+    Compatible,
+}
+
+/// The versions a pinned mod may be held to, such as `1.2.0.0`, `=1.2`, `>=1.2, <2` or `~1.2`;
+/// A version satisfies it if it meets every comma separated requirement
+#[derive(Clone, Debug, PartialEq)]
+pub struct Constraint(Vec<(Comparison, String)>);
+
+impl FromStr for Constraint {
+    type Err = String;
+
+    fn from_str(constraint: &str) -> Result<Self, Self::Err> {
+        let mut requirements = vec![];
+        for requirement in constraint.split(',') {
+            let requirement = requirement.trim();
+            let (comparison, version) = [
+                (">=", Comparison::GreaterOrEqual),
+                ("<=", Comparison::LessOrEqual),
+                (">", Comparison::Greater),
+                ("<", Comparison::Less),
+                ("=", Comparison::Exact),
+                ("~", Comparison::Compatible),
+            ]
+            .iter()
+            .find_map(|(prefix, comparison)| {
+                requirement
+                    .strip_prefix(prefix)
+                    .map(|version| (*comparison, version.trim()))
+            })
+            .unwrap_or((Comparison::Exact, requirement));
+            if version.is_empty() || version.contains(char::is_whitespace) {
+                return Err(format!("invalid version constraint {:?}", constraint));
+            }
+            requirements.push((comparison, version.to_string()));
+        }
+
+        Ok(Constraint(requirements))
+    }
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let requirements: Vec<String> = self
+            .0
+            .iter()
+            .map(|(comparison, version)| {
+                let prefix = match comparison {
+                    Comparison::Exact => "",
+                    Comparison::Greater => ">",
+                    Comparison::GreaterOrEqual => ">=",
+                    Comparison::Less => "<",
+                    Comparison::LessOrEqual => "<=",
+                    Comparison::Compatible => "~",
+                };
+                format!("{}{}", prefix, version)
+            })
+            .collect();
+        write!(f, "{}", requirements.join(", "))
+    }
+}
+
+impl Constraint {
+    /// Whether a version meets every requirement of the constraint
+    /// # Arguments
+    /// * `version` - The version to check
+    pub fn matches(&self, version: &str) -> bool {
+        self.0.iter().all(|(comparison, required)| {
+            let ordering = compare(version, required.as_str());
+            match comparison {
+                Comparison::Exact => ordering == Ordering::Equal,
+                Comparison::Greater => ordering == Ordering::Greater,
+                Comparison::GreaterOrEqual => ordering != Ordering::Less,
+                Comparison::Less => ordering == Ordering::Less,
+                Comparison::LessOrEqual => ordering != Ordering::Greater,
+                Comparison::Compatible => {
+                    // Every component but the last must match, so ~1.2.0 allows 1.2.x but not 1.3
+                    let prefix: Vec<&str> = required.split(['.', '-']).collect();
+                    let prefix = prefix[..prefix.len().saturating_sub(1).max(1)].join(".");
+                    let truncated: Vec<&str> = version
+                        .trim()
+                        .split(['.', '-'])
+                        .take(prefix.split('.').count())
+                        .collect();
+                    ordering != Ordering::Less
+                        && compare(truncated.join(".").as_str(), prefix.as_str()) == Ordering::Equal
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constraint(text: &str) -> Constraint {
+        Constraint::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn parses_requirements() {
+        assert_eq!(
+            constraint(">=1.2, <2"),
+            Constraint(vec![
+                (Comparison::GreaterOrEqual, "1.2".to_string()),
+                (Comparison::Less, "2".to_string()),
+            ])
+        );
+        assert_eq!(
+            constraint("1.2.0.0"),
+            Constraint(vec![(Comparison::Exact, "1.2.0.0".to_string())])
+        );
+        assert_eq!(
+            constraint(" = 1.2 "),
+            Constraint(vec![(Comparison::Exact, "1.2".to_string())])
+        );
+        assert_eq!(
+            constraint("<=1.4,>1.1,~1.3"),
+            Constraint(vec![
+                (Comparison::LessOrEqual, "1.4".to_string()),
+                (Comparison::Greater, "1.1".to_string()),
+                (Comparison::Compatible, "1.3".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_constraints() {
+        for text in ["", ">=", "1.2,", ">= 1 2", "~"] {
+            assert!(
+                Constraint::from_str(text).is_err(),
+                "{:?} was accepted",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn displays_parsed_constraints() {
+        assert_eq!(constraint(">= 1.2 ,<2").to_string(), ">=1.2, <2");
+        assert_eq!(constraint("=1.2").to_string(), "1.2");
+        assert_eq!(constraint("~1.2.0").to_string(), "~1.2.0");
+    }
+
+    #[test]
+    fn matches_comparisons() {
+        let range = constraint(">=1.2, <2");
+        assert!(range.matches("1.2"));
+        assert!(range.matches("1.10.0.0"));
+        assert!(!range.matches("1.1.9"));
+        assert!(!range.matches("2.0.0.0"));
+
+        assert!(constraint("1.2").matches("1.2.0.0"));
+        assert!(!constraint("1.2").matches("1.2.0.1"));
+        assert!(constraint(">1.2").matches("1.2.0.1"));
+        assert!(constraint("<=1.2").matches("1.2"));
+        assert!(!constraint("<=1.2").matches("1.2.1"));
+    }
+
+    #[test]
+    fn compatible_keeps_all_but_the_last_component() {
+        let compatible = constraint("~1.2.0");
+        assert!(compatible.matches("1.2.0"));
+        assert!(compatible.matches("1.2.7"));
+        assert!(compatible.matches("1.2.7.3"));
+        assert!(!compatible.matches("1.3.0"));
+        assert!(!compatible.matches("1.1.9"));
+
+        let compatible = constraint("~1.2.3.4");
+        assert!(compatible.matches("1.2.3.9"));
+        assert!(!compatible.matches("1.2.3.3"));
+        assert!(!compatible.matches("1.2.4.0"));
+
+        // A single component is kept whole, so ~1 allows any 1.x
+        let compatible = constraint("~1");
+        assert!(compatible.matches("1.9"));
+        assert!(!compatible.matches("2.0"));
+    }
+}
@@ -0,0 +1,337 @@
+use directories::BaseDirs;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The Steam app ID of Hollow Knight
+pub const APP_ID: &str = "367520";
+
+/// The folder Steam installs Hollow Knight into when its app manifest does not say
+const DEFAULT_INSTALL_DIR: &str = "Hollow Knight";
+
+/// A value in a Valve KeyValues (VDF) file such as `libraryfolders.vdf` or an app manifest
+#[derive(Clone, Debug, PartialEq)]
+pub enum Vdf {
+    Value(String),
+    Section(Vec<(String, Vdf)>),
+}
+
+impl Vdf {
+    /// Get the value of a key in a section; Keys are compared ignoring case like Steam does
+    /// # Arguments
+    /// * `key` - The key to look up
+    pub fn get(&self, key: &str) -> Option<&Vdf> {
+        match self {
+            Vdf::Section(pairs) => pairs
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value),
+            Vdf::Value(_) => None,
+        }
+    }
+
+    /// Get the text of a value, or None for a section
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Vdf::Value(value) => Some(value.as_str()),
+            Vdf::Section(_) => None,
+        }
+    }
+}
+
+/// A token of a KeyValues file
+#[derive(Debug, PartialEq)]
+enum Token {
+    Text(String),
+    Open,
+    Close,
+}
+
+/// Split the text of a KeyValues file into tokens, skipping comments and conditionals such as
+/// `[$WIN32]`; Returns None if a quoted string is not closed
+/// # Arguments
+/// * `text` - The contents of the file
+fn tokenize(text: &str) -> Option<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => tokens.push(Token::Open),
+            '}' => tokens.push(Token::Close),
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => match chars.next()? {
+                            'n' => value.push('\n'),
+                            't' => value.push('\t'),
+                            escaped => value.push(escaped),
+                        },
+                        c => value.push(c),
+                    }
+                }
+                tokens.push(Token::Text(value));
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut value = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || next == '{' || next == '}' || next == '"' {
+                        break;
+                    }
+                    value.push(next);
+                    chars.next();
+                }
+                if !(value.starts_with('[') && value.ends_with(']')) {
+                    tokens.push(Token::Text(value));
+                }
+            }
+        }
+    }
+
+    Some(tokens)
+}
+
+/// Parse the key-value pairs of a section up to its closing brace, or up to the end of the file
+/// for the top level section
+/// # Arguments
+/// * `tokens` - The remaining tokens of the file
+/// * `nested` - Whether the section is inside another one and must be closed
+fn parse_section(
+    tokens: &mut std::vec::IntoIter<Token>,
+    nested: bool,
+) -> Option<Vec<(String, Vdf)>> {
+    let mut pairs = vec![];
+    loop {
+        let key = match tokens.next() {
+            Some(Token::Text(key)) => key,
+            Some(Token::Close) if nested => return Some(pairs),
+            None if !nested => return Some(pairs),
+            _ => return None,
+        };
+        let value = match tokens.next()? {
+            Token::Text(value) => Vdf::Value(value),
+            Token::Open => Vdf::Section(parse_section(tokens, true)?),
+            Token::Close => return None,
+        };
+        pairs.push((key, value));
+    }
+}
+
+/// Parse the contents of a KeyValues file into its top level section; Returns None if the file
+/// is malformed
+/// # Arguments
+/// * `text` - The contents of the file
+pub fn parse_vdf(text: &str) -> Option<Vdf> {
+    let mut tokens = tokenize(text)?.into_iter();
+    parse_section(&mut tokens, false).map(Vdf::Section)
+}
+
+/// The folders Steam may be installed in on this platform that exist, including the Flatpak and
+/// Snap installs on Linux; Folders that link to the same place are only listed once
+/// # Arguments
+/// * `base_dir` - The base directories of the current user
+pub fn steam_roots(base_dir: &BaseDirs) -> Vec<PathBuf> {
+    let home = base_dir.home_dir();
+    let candidates: Vec<PathBuf> = match std::env::consts::OS {
+        "windows" => (b'A'..=b'Z')
+            .flat_map(|letter| {
+                let drive = format!("{}:/", letter as char);
+                [
+                    Path::new(drive.as_str()).join("Program Files (x86)/Steam"),
+                    Path::new(drive.as_str()).join("Program Files/Steam"),
+                ]
+            })
+            .collect(),
+        "macos" => vec![base_dir.data_dir().join("Steam")],
+        _ => vec![
+            base_dir.data_dir().join("Steam"),
+            home.join(".steam/steam"),
+            home.join(".steam/root"),
+            home.join(".local/share/Steam"),
+            // Flatpak
+            home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"),
+            home.join(".var/app/com.valvesoftware.Steam/.steam/steam"),
+            // Snap
+            home.join("snap/steam/common/.local/share/Steam"),
+            home.join("snap/steam/common/.steam/steam"),
+        ],
+    };
+
+    let mut roots: Vec<PathBuf> = vec![];
+    for candidate in candidates {
+        if let Ok(root) = dunce::canonicalize(candidate.as_path()) {
+            if root.join("steamapps").is_dir() && !roots.contains(&root) {
+                roots.push(root);
+            }
+        }
+    }
+
+    roots
+}
+
+/// The library folders configured in a Steam install, starting with the install itself
+/// # Arguments
+/// * `steam_root` - The folder Steam is installed in
+pub fn library_folders(steam_root: &Path) -> Vec<PathBuf> {
+    let mut libraries = vec![steam_root.to_path_buf()];
+    let vdf_path = steam_root.join("steamapps").join("libraryfolders.vdf");
+    let vdf = match fs::read_to_string(vdf_path.as_path()) {
+        Ok(text) => match parse_vdf(text.as_str()) {
+            Some(vdf) => vdf,
+            None => {
+                log::warn!("Failed to parse {:?}", vdf_path);
+                return libraries;
+            }
+        },
+        Err(_) => return libraries,
+    };
+
+    if let Some(Vdf::Section(folders)) = vdf.get("libraryfolders") {
+        for (key, folder) in folders {
+            if key.parse::<u32>().is_err() {
+                continue;
+            }
+            // Older versions of Steam list the path itself rather than a section with it
+            let path = match folder {
+                Vdf::Value(path) => Some(path.as_str()),
+                Vdf::Section(_) => folder.get("path").and_then(Vdf::as_str),
+            };
+            if let Some(path) = path {
+                let path = PathBuf::from(path);
+                let path = dunce::canonicalize(path.as_path()).unwrap_or(path);
+                if !libraries.contains(&path) {
+                    libraries.push(path);
+                }
+            }
+        }
+    }
+
+    libraries
+}
+
+/// Find Hollow Knight in the Steam libraries of the given Steam installs by reading its app
+/// manifest in each library; Returns the game folders that exist
+/// # Arguments
+/// * `steam_roots` - The folders Steam is installed in
+pub fn find_games(steam_roots: &[PathBuf]) -> Vec<PathBuf> {
+    let mut games: Vec<PathBuf> = vec![];
+    for library in steam_roots.iter().flat_map(|root| library_folders(root)) {
+        let steamapps = library.join("steamapps");
+        let manifest_path = steamapps.join(format!("appmanifest_{}.acf", APP_ID));
+        let install_dir = fs::read_to_string(manifest_path.as_path())
+            .ok()
+            .and_then(|text| parse_vdf(text.as_str()))
+            .and_then(|manifest| {
+                manifest
+                    .get("AppState")
+                    .and_then(|app_state| app_state.get("installdir"))
+                    .and_then(Vdf::as_str)
+                    .map(|install_dir| install_dir.to_string())
+            })
+            .unwrap_or_else(|| DEFAULT_INSTALL_DIR.to_string());
+        let game_path = steamapps.join("common").join(install_dir);
+        log::info!("Checking Steam library path {:?}", game_path);
+        if game_path.is_dir() && !games.contains(&game_path) {
+            games.push(game_path);
+        }
+    }
+
+    games
+}
+
+/// Read the Steam build ID of a game installed in a Steam library from its app manifest
+/// # Arguments
+/// * `game_path` - The game folder, inside of a library's `steamapps/common` folder
+pub fn build_id(game_path: &Path) -> Option<String> {
+    let steamapps = game_path.parent()?.parent()?;
+    let text = fs::read_to_string(steamapps.join(format!("appmanifest_{}.acf", APP_ID))).ok()?;
+    parse_vdf(text.as_str())?
+        .get("AppState")?
+        .get("buildid")?
+        .as_str()
+        .map(|build_id| build_id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_library_folders() {
+        let vdf = parse_vdf(
+            r#"
+            "libraryfolders"
+            {
+                "0"
+                {
+                    "path"		"/home/user/.local/share/Steam"
+                    "apps"
+                    {
+                        "228980"		"316144294"
+                    }
+                }
+                // A second library on another drive
+                "1"
+                {
+                    "path"		"D:\\Games\\Steam"
+                    "apps" { "367520" "9313201843" }
+                }
+            }
+            "#,
+        )
+        .unwrap();
+
+        let folders = vdf.get("LibraryFolders").unwrap();
+        assert_eq!(
+            folders.get("1").and_then(|folder| folder.get("path")),
+            Some(&Vdf::Value("D:\\Games\\Steam".to_string()))
+        );
+        assert_eq!(
+            folders
+                .get("1")
+                .and_then(|folder| folder.get("apps"))
+                .and_then(|apps| apps.get("367520"))
+                .and_then(Vdf::as_str),
+            Some("9313201843")
+        );
+    }
+
+    #[test]
+    fn parses_app_manifest() {
+        let vdf = parse_vdf(
+            r#""AppState"
+            {
+                "appid"		"367520"
+                "name"		"Hollow Knight"
+                "installdir"		"Hollow Knight"
+                "InstalledDepots" { }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vdf.get("appstate")
+                .and_then(|app_state| app_state.get("installdir"))
+                .and_then(Vdf::as_str),
+            Some("Hollow Knight")
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        assert_eq!(
+            parse_vdf(r#""libraryfolders" { "0" { "path" "/games" }"#),
+            None
+        );
+        assert_eq!(parse_vdf(r#""libraryfolders" { "path "#), None);
+        assert_eq!(parse_vdf(r#""a" "b" }"#), None);
+    }
+}
@@ -0,0 +1,32 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Tells apart the folders of tests that run at the same time in one process
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A fresh folder in the system temp folder that is deleted when dropped
+pub struct TestDir(pub PathBuf);
+
+impl TestDir {
+    /// Create an empty folder whose name starts with the given name
+    /// # Arguments
+    /// * `name` - What the folder is for
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "hkdl-{}-{}-{}",
+            name,
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = fs::remove_dir_all(path.as_path());
+        fs::create_dir_all(path.as_path()).unwrap();
+        TestDir(path)
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(self.0.as_path());
+    }
+}